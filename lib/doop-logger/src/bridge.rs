@@ -0,0 +1,375 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::{Field, Level, Log, Message, Time};
+
+/// A set of per-target minimum levels for logs emitted by other crates through the `log` or
+/// `tracing` ecosystems.
+///
+/// Targets are written as comma-separated directives, where each directive is either a default
+/// level or a `target=level` pair, and `off` disables a target entirely; for example,
+/// `info,twilight_gateway=warn,rustls=off`. The most specific matching target is used, where
+/// `a` matches both `a` and `a::b`. Targets without a directive default to
+/// [`DEFAULT_LEVEL`](<Targets::DEFAULT_LEVEL>), and trace logs are treated as debug logs.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Targets {
+    /// The minimum level of targets without a directive, or [`None`] if they are disabled.
+    default: Option<Level>,
+    /// The minimum level of each target, or [`None`] if it is disabled.
+    targets: Vec<(Box<str>, Option<Level>)>,
+}
+
+impl Targets {
+    /// The minimum level of targets without a directive, unless a default level is given.
+    pub const DEFAULT_LEVEL: Option<Level> = Some(Level::WARN);
+
+    /// Creates a new [`Targets`] that outputs every target at or above the given minimum level.
+    #[must_use]
+    pub const fn new(default: Option<Level>) -> Self {
+        Self { default, targets: Vec::new() }
+    }
+
+    /// Returns these [`Targets`] with the given target's minimum level, or with the target
+    /// disabled if no level is given.
+    #[must_use]
+    pub fn with(mut self, target: impl Into<Box<str>>, level: Option<Level>) -> Self {
+        let target = target.into();
+
+        self.targets.retain(|(t, _)| t != &target);
+        self.targets.push((target, level));
+
+        self
+    }
+
+    /// Returns the minimum level of the given target, or [`None`] if it is disabled.
+    #[must_use]
+    pub fn level(&self, target: &str) -> Option<Level> {
+        let matches = |t: &str| {
+            target.strip_prefix(t).is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        };
+
+        self.targets
+            .iter()
+            .filter(|(t, _)| matches(t))
+            .max_by_key(|(t, _)| t.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    /// Returns whether logs of the given target and level are output.
+    #[must_use]
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        self.level(target).is_some_and(|minimum| level >= minimum)
+    }
+
+    /// Returns the lowest minimum level of any target, or [`None`] if every target is disabled.
+    #[must_use]
+    pub fn lowest(&self) -> Option<Level> {
+        self.targets.iter().map(|(_, level)| *level).chain([self.default]).flatten().min()
+    }
+}
+
+impl Default for Targets {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_LEVEL)
+    }
+}
+
+impl Display for Targets {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = |level: Option<Level>| level.map_or("off", |l| l.name);
+
+        f.write_str(name(self.default))?;
+
+        for (target, level) in &self.targets {
+            write!(f, ",{target}={}", name(*level))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Targets {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let level = |s: &str| {
+            if s.eq_ignore_ascii_case("off") {
+                Ok(None)
+            } else {
+                s.parse::<Level>().map(Some)
+            }
+        };
+        let mut targets = Self::default();
+
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, value)) => {
+                    targets = targets.with(target.trim(), level(value.trim())?);
+                }
+                None => targets.default = level(directive)?,
+            }
+        }
+
+        Ok(targets)
+    }
+}
+
+/// Forwards logs emitted by other crates into the logging thread.
+#[derive(Debug)]
+struct Bridge {
+    /// The minimum level of each target.
+    targets: Targets,
+}
+
+impl Bridge {
+    /// Queues a log emitted by the given target, dropping it if the logging thread is unavailable.
+    fn queue(target: &str, kind: Level, text: impl Display, mut fields: Vec<Field>) {
+        let Some(sender) = crate::SENDER.get() else {
+            return;
+        };
+
+        fields.insert(0, Field::new("target", target));
+
+        // Other crates cannot handle logging errors, so they are ignored.
+        sender.send(Message::Queue(Log::new(Time::now(), kind, text).with_fields(fields))).ok();
+    }
+}
+
+/// Installs a `log` implementation that forwards logs emitted through the `log` crate into the
+/// logging thread, filtered using the given targets.
+///
+/// # Errors
+///
+/// This function will return an error if a `log` implementation has already been installed.
+#[cfg(feature = "log")]
+pub fn install_log_bridge(targets: Targets) -> Result<(), log::SetLoggerError> {
+    let max_level = match targets.lowest() {
+        None => log::LevelFilter::Off,
+        Some(level) if level <= Level::DEBUG => log::LevelFilter::Trace,
+        Some(level) if level <= Level::INFO => log::LevelFilter::Info,
+        Some(level) if level <= Level::WARN => log::LevelFilter::Warn,
+        Some(_) => log::LevelFilter::Error,
+    };
+
+    log::set_boxed_logger(Box::new(Bridge { targets }))?;
+    log::set_max_level(max_level);
+
+    Ok(())
+}
+
+#[cfg(feature = "log")]
+impl Bridge {
+    /// Returns the [`Level`] that corresponds to the given `log` level.
+    const fn log_level(level: log::Level) -> Level {
+        match level {
+            log::Level::Error => Level::ERROR,
+            log::Level::Warn => Level::WARN,
+            log::Level::Info => Level::INFO,
+            log::Level::Debug | log::Level::Trace => Level::DEBUG,
+        }
+    }
+}
+
+#[cfg(feature = "log")]
+impl log::Log for Bridge {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.targets.enabled(metadata.target(), Self::log_level(metadata.level()))
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        Self::queue(record.target(), Self::log_level(record.level()), record.args(), vec![]);
+    }
+
+    fn flush(&self) {
+        if let Some(sender) = crate::SENDER.get() {
+            sender.send(Message::Flush).ok();
+        }
+    }
+}
+
+/// Installs a global `tracing` subscriber that forwards events emitted through the `tracing`
+/// crate into the logging thread, filtered using the given targets.
+///
+/// Spans are not recorded.
+///
+/// # Errors
+///
+/// This function will return an error if a global `tracing` subscriber has already been installed.
+#[cfg(feature = "tracing")]
+pub fn install_tracing_bridge(
+    targets: Targets,
+) -> Result<(), tracing::subscriber::SetGlobalDefaultError> {
+    tracing::subscriber::set_global_default(Bridge { targets })
+}
+
+#[cfg(feature = "tracing")]
+impl Bridge {
+    /// Returns the [`Level`] that corresponds to the given `tracing` level.
+    const fn tracing_level(level: tracing::Level) -> Level {
+        match level {
+            tracing::Level::ERROR => Level::ERROR,
+            tracing::Level::WARN => Level::WARN,
+            tracing::Level::INFO => Level::INFO,
+            _ => Level::DEBUG,
+        }
+    }
+}
+
+/// Collects the message and fields of a `tracing` event.
+#[cfg(feature = "tracing")]
+#[derive(Debug, Default)]
+struct Visitor {
+    /// The event's message.
+    message: String,
+    /// The event's fields.
+    fields: Vec<Field>,
+}
+
+#[cfg(feature = "tracing")]
+impl Visitor {
+    /// Records the given field.
+    fn push(&mut self, field: &tracing::field::Field, value: impl Into<crate::Value>) {
+        self.fields.push(Field::new(field.name(), value));
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl tracing::field::Visit for Visitor {
+    fn record_f64(&mut self, field: &tracing::field::Field, value: f64) {
+        self.push(field, value);
+    }
+
+    fn record_i64(&mut self, field: &tracing::field::Field, value: i64) {
+        self.push(field, value);
+    }
+
+    fn record_u64(&mut self, field: &tracing::field::Field, value: u64) {
+        self.push(field, value);
+    }
+
+    fn record_bool(&mut self, field: &tracing::field::Field, value: bool) {
+        self.push(field, value);
+    }
+
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        if field.name() == "message" {
+            value.clone_into(&mut self.message);
+        } else {
+            self.push(field, value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        } else {
+            self.fields.push(Field::debug(field.name(), &value));
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl tracing::Subscriber for Bridge {
+    fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+        metadata.is_event()
+            && self.targets.enabled(metadata.target(), Self::tracing_level(*metadata.level()))
+    }
+
+    fn max_level_hint(&self) -> Option<tracing::level_filters::LevelFilter> {
+        use tracing::level_filters::LevelFilter;
+
+        Some(match self.targets.lowest() {
+            None => LevelFilter::OFF,
+            Some(level) if level <= Level::DEBUG => LevelFilter::TRACE,
+            Some(level) if level <= Level::INFO => LevelFilter::INFO,
+            Some(level) if level <= Level::WARN => LevelFilter::WARN,
+            Some(_) => LevelFilter::ERROR,
+        })
+    }
+
+    fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        // Spans are never enabled, so their identifiers are never used.
+        tracing::span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+    fn event(&self, event: &tracing::Event<'_>) {
+        let metadata = event.metadata();
+        let mut visitor = Visitor::default();
+
+        event.record(&mut visitor);
+
+        Self::queue(
+            metadata.target(),
+            Self::tracing_level(*metadata.level()),
+            visitor.message,
+            visitor.fields,
+        );
+    }
+
+    fn enter(&self, _: &tracing::span::Id) {}
+
+    fn exit(&self, _: &tracing::span::Id) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Targets;
+    use crate::Level;
+
+    #[test]
+    fn parse_targets() -> Result<(), String> {
+        let targets = "info, twilight_gateway = warn,rustls=OFF,,".parse::<Targets>()?;
+        let expected = Targets::new(Some(Level::INFO))
+            .with("twilight_gateway", Some(Level::WARN))
+            .with("rustls", None);
+
+        assert_eq!(targets, expected);
+        assert_eq!(targets.to_string(), "info,twilight_gateway=warn,rustls=off");
+        assert_eq!(targets.to_string().parse::<Targets>()?, targets);
+        assert_eq!("".parse::<Targets>()?, Targets::default());
+
+        // Repeated targets keep their last directive.
+        let repeated = Targets::default().with("a", Some(Level::ERROR));
+
+        assert_eq!("a=info,a=error".parse::<Targets>()?, repeated);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_invalid_targets() {
+        assert!("verbose".parse::<Targets>().is_err());
+        assert!("rustls=loud".parse::<Targets>().is_err());
+        assert!("rustls=".parse::<Targets>().is_err());
+    }
+
+    #[test]
+    fn filter_by_target() {
+        let targets = Targets::new(Some(Level::WARN))
+            .with("twilight", Some(Level::INFO))
+            .with("twilight::http", Some(Level::ERROR))
+            .with("rustls", None);
+
+        assert!(targets.enabled("tokio", Level::WARN));
+        assert!(!targets.enabled("tokio", Level::INFO));
+        assert!(targets.enabled("twilight::gateway", Level::INFO));
+        assert!(!targets.enabled("twilight::http::client", Level::WARN));
+        assert!(!targets.enabled("rustls", Level::ERROR));
+
+        // Targets only match whole path segments.
+        assert!(!targets.enabled("twilight_cache", Level::INFO));
+        assert!(!targets.enabled("rustls_pemfile", Level::INFO));
+        assert!(targets.enabled("rustls_pemfile", Level::WARN));
+
+        assert_eq!(targets.lowest(), Some(Level::INFO));
+        assert_eq!(Targets::new(None).with("a", None).lowest(), None);
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt::{Debug, Display, Formatter};
+
+/// A structured key-value field attached to a log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    /// The field's key.
+    pub key: &'static str,
+    /// The field's value.
+    pub value: Value,
+}
+
+impl Field {
+    /// Creates a new [`Field`].
+    pub fn new(key: &'static str, value: impl Into<Value>) -> Self {
+        Self { key, value: value.into() }
+    }
+
+    /// Creates a new [`Field`] containing the [`Display`] text of the given value.
+    pub fn display(key: &'static str, value: &impl Display) -> Self {
+        Self::new(key, value.to_string())
+    }
+
+    /// Creates a new [`Field`] containing the [`Debug`] text of the given value.
+    pub fn debug(key: &'static str, value: &impl Debug) -> Self {
+        Self::new(key, format!("{value:?}"))
+    }
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.key, self.value)
+    }
+}
+
+/// The typed value of a log [`Field`].
+///
+/// Values are displayed as `logfmt` values, where strings are quoted and escaped if they contain
+/// whitespace, control characters, quotes, or equals signs.
+#[derive(Clone, Debug)]
+pub enum Value {
+    /// A missing value.
+    Null,
+    /// A boolean value.
+    Bool(bool),
+    /// A signed integer value.
+    Int(i64),
+    /// An unsigned integer value.
+    Uint(u64),
+    /// A floating-point value.
+    Float(f64),
+    /// A string value.
+    Str(Box<str>),
+}
+
+impl Value {
+    /// Returns this value as a JSON value, where non-finite floats become `null`.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Self::Null => serde_json::Value::Null,
+            Self::Bool(value) => (*value).into(),
+            Self::Int(value) => (*value).into(),
+            Self::Uint(value) => (*value).into(),
+            Self::Float(value) => (*value).into(),
+            Self::Str(value) => (&(**value)).into(),
+        }
+    }
+
+    /// Returns whether the given string can be displayed without being quoted.
+    fn is_bare(string: &str) -> bool {
+        let reserved =
+            |c: char| c.is_whitespace() || c.is_control() || matches!(c, '"' | '=' | '\\');
+
+        !string.is_empty() && string != "null" && !string.contains(reserved)
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Null, Self::Null) => true,
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Int(a), Self::Int(b)) => a == b,
+            (Self::Uint(a), Self::Uint(b)) => a == b,
+            // Floats are compared by their bits so that every value is equal to itself.
+            (Self::Float(a), Self::Float(b)) => a.to_bits() == b.to_bits(),
+            (Self::Str(a), Self::Str(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Value {}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Uint(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value}"),
+            Self::Str(value) if Self::is_bare(value) => f.write_str(value),
+            Self::Str(value) => write!(f, "{value:?}"),
+        }
+    }
+}
+
+/// Implements [`From`] for [`Value`] using the given variant.
+macro_rules! from {
+    ($($variant:ident($($type:ty),+);)+) => {$($(
+        impl From<$type> for Value {
+            fn from(value: $type) -> Self {
+                Self::$variant(value.into())
+            }
+        }
+    )+)+};
+}
+
+from! {
+    Bool(bool);
+    Int(i8, i16, i32, i64);
+    Uint(u8, u16, u32, u64);
+    Float(f32, f64);
+    Str(&str, String, Box<str>);
+}
+
+impl From<isize> for Value {
+    fn from(value: isize) -> Self {
+        i64::try_from(value).map_or_else(|_| Self::Str(value.to_string().into()), Self::Int)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        u64::try_from(value).map_or_else(|_| Self::Str(value.to_string().into()), Self::Uint)
+    }
+}
+
+impl From<char> for Value {
+    fn from(value: char) -> Self {
+        Self::Str(value.to_string().into_boxed_str())
+    }
+}
+
+impl<T: Into<Self>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Field, Value};
+
+    #[test]
+    fn display_escapes_strings() {
+        assert_eq!(Field::new("name", "plain").to_string(), "name=plain");
+        assert_eq!(Field::new("name", "two words").to_string(), r#"name="two words""#);
+        assert_eq!(Field::new("name", "a=b").to_string(), r#"name="a=b""#);
+        assert_eq!(Field::new("name", "say \"hi\"").to_string(), r#"name="say \"hi\"""#);
+        assert_eq!(Field::new("name", "tab\there").to_string(), r#"name="tab\there""#);
+
+        // Empty strings and strings that look like a missing value are always quoted.
+        assert_eq!(Field::new("name", "").to_string(), r#"name="""#);
+        assert_eq!(Field::new("name", "null").to_string(), r#"name="null""#);
+        assert_eq!(Field::new("name", None::<u8>).to_string(), "name=null");
+    }
+
+    #[test]
+    fn display_typed_values() {
+        assert_eq!(Field::new("count", 3_u8).to_string(), "count=3");
+        assert_eq!(Field::new("delta", -3_i32).to_string(), "delta=-3");
+        assert_eq!(Field::new("ratio", 0.5).to_string(), "ratio=0.5");
+        assert_eq!(Field::new("open", true).to_string(), "open=true");
+        assert_eq!(Field::display("id", &42).value, Value::from("42"));
+        assert_eq!(Field::debug("name", &"text").value, Value::from("\"text\""));
+    }
+
+    #[test]
+    fn json_values() {
+        assert_eq!(Value::Null.to_json(), serde_json::Value::Null);
+        assert_eq!(Value::from(3_u64).to_json(), serde_json::json!(3));
+        assert_eq!(Value::from(-3_i64).to_json(), serde_json::json!(-3));
+        assert_eq!(Value::from("two words").to_json(), serde_json::json!("two words"));
+
+        // JSON cannot represent non-finite floats.
+        assert_eq!(Value::from(f64::NAN).to_json(), serde_json::Value::Null);
+        assert_eq!(Value::from(f64::NAN), Value::from(f64::NAN));
+    }
+}
@@ -3,19 +3,37 @@
 #![warn(clippy::nursery, clippy::todo, clippy::pedantic, missing_docs)]
 #![allow(clippy::module_name_repetitions)]
 
+use std::cmp::Ordering;
 use std::fmt::Display;
+use std::hash::{Hash, Hasher};
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
+use std::str::FromStr;
 use std::sync::OnceLock;
 use std::time::Duration;
 
 use crossbeam_channel::{SendError, Sender};
 use doop_threads::{AutoJoin, Consumer, HandledThread, SenderThread};
 use owo_colors::{OwoColorize, Stream};
+use time::format_description::well_known::Rfc3339;
 use time::format_description::FormatItem;
 use time::macros::format_description;
-use time::OffsetDateTime;
+use time::{Date, OffsetDateTime};
+
+#[cfg(feature = "log")]
+pub use crate::bridge::install_log_bridge;
+#[cfg(feature = "tracing")]
+pub use crate::bridge::install_tracing_bridge;
+#[cfg(any(feature = "log", feature = "tracing"))]
+pub use crate::bridge::Targets;
+pub use crate::field::{Field, Value};
+pub use crate::rotate::Rotation;
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+mod bridge;
+mod field;
+mod rotate;
 
 /// The type of the returned logging thread handle.
 pub type LogThread = AutoJoin<Consumer<Message, std::io::Result<()>>, std::io::Result<()>>;
@@ -44,6 +62,21 @@ pub fn queue(kind: Level, text: impl Display) -> Result<(), SendError<Message>>
     sender().send(Message::Queue(log))
 }
 
+/// Queues a log with the given structured fields.
+///
+/// # Errors
+///
+/// This function will return an error if the logging thread is closed.
+pub fn queue_with_fields(
+    kind: Level,
+    text: impl Display,
+    fields: impl Into<Box<[Field]>>,
+) -> Result<(), SendError<Message>> {
+    let log = Log::new(Time::now(), kind, text).with_fields(fields);
+
+    sender().send(Message::Queue(log))
+}
+
 /// Flushes the logger queue.
 ///
 /// # Errors
@@ -53,6 +86,17 @@ pub fn flush() -> Result<(), SendError<Message>> {
     sender().send(Message::Flush)
 }
 
+/// Sets the minimum level of logs that are output to the given sink.
+///
+/// Logs that were queued before the change are output using the previous minimum level.
+///
+/// # Errors
+///
+/// This function will return an error if the logging thread is closed.
+pub fn set_level(sink: Sink, level: Level) -> Result<(), SendError<Message>> {
+    sender().send(Message::SetLevel(sink, level))
+}
+
 /// Closes the logging thread.
 ///
 /// If you call this method, the thread cannot be safely re-initialized and attempting to log again
@@ -79,6 +123,9 @@ pub fn close() -> Result<(), SendError<Message>> {
 /// This function will return an error if the thread cannot be initialized.
 pub fn install(config: Config, dir: impl AsRef<Path>) -> std::io::Result<LogThread> {
     let mut logger = Logger::new(config, dir);
+
+    logger.tidy()?;
+
     let timeout = Duration::from_millis(logger.config.stale_time);
 
     let thread = Consumer::spawn("logger", move |receiver| {
@@ -88,6 +135,7 @@ pub fn install(config: Config, dir: impl AsRef<Path>) -> std::io::Result<LogThre
             match receiver.recv_timeout(timeout) {
                 Ok(Message::Queue(log)) if !logger.config.disabled() => logger.queue(log)?,
                 Ok(Message::Flush) | Err(Timeout) if !logger.is_empty() => logger.flush()?,
+                Ok(Message::SetLevel(sink, level)) => logger.set_level(sink, level)?,
                 Ok(Message::Close) | Err(Disconnected) => {
                     drop(receiver);
 
@@ -114,6 +162,8 @@ pub enum Message {
     Queue(Log),
     /// Flushes the logger.
     Flush,
+    /// Sets the minimum level of logs that are output to a sink.
+    SetLevel(Sink, Level),
     /// Closes the logging thread.
     Close,
 }
@@ -123,8 +173,14 @@ pub enum Message {
 pub struct Logger {
     /// The logger's configuration.
     config: Config,
+    /// The logger's output directory.
+    dir: Box<Path>,
     /// The logger's output file path.
     path: Box<Path>,
+    /// The local date on which the output file was started.
+    started: Date,
+    /// The size of the output file in bytes.
+    size: u64,
     /// The logger's output queue.
     queue: Vec<Log>,
 }
@@ -145,11 +201,29 @@ impl Logger {
     #[must_use]
     pub fn new(config: Config, dir: impl AsRef<Path>) -> Self {
         let time = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+        let dir: Box<Path> = dir.as_ref().into();
+        let path = Self::file_path(&dir, time, config.format);
+
+        Self {
+            config,
+            dir,
+            path,
+            started: time.date(),
+            size: 0,
+            queue: Vec::with_capacity(config.queue_size),
+        }
+    }
+
+    /// Returns the path of a log file of the given format that was started at the given time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the defined file name formatter is invalid.
+    fn file_path(dir: &Path, time: OffsetDateTime, format: Format) -> Box<Path> {
         #[allow(clippy::unwrap_used)] // Will only fail if the format constant is invalid.
         let file = time.format(Self::FILENAME_FORMAT).unwrap();
-        let path = dir.as_ref().join(file).with_extension("txt").into_boxed_path();
 
-        Self { config, path, queue: Vec::with_capacity(config.queue_size) }
+        dir.join(file).with_extension(format.extension()).into_boxed_path()
     }
 
     /// Returns whether the queue is empty.
@@ -170,6 +244,10 @@ impl Logger {
     ///
     /// This function will return an error if log(s) failed to output during a flush.
     pub fn queue(&mut self, log: Log) -> std::io::Result<()> {
+        if !self.config.accepts(log.kind) {
+            return Ok(());
+        }
+
         self.queue.push(log);
 
         if self.is_full() { self.flush() } else { Ok(()) }
@@ -181,53 +259,105 @@ impl Logger {
     ///
     /// This function will return an error if log(s) failed to output.
     pub fn flush(&mut self) -> std::io::Result<()> {
+        let Config { print, write, print_level, write_level, format, .. } = self.config;
         let display_color = self.config.support_color();
-        let mut iterator = self.queue.drain(..).fuse().peekable();
-        let mut blocks = vec![];
-
-        while let Some(log) = iterator.next() {
-            let color = display_color.then(|| log.display(Some(log.stream())));
-            let mut block = (log.kind.error, log.display(None), color);
-
-            #[allow(unsafe_code)]
-            while iterator.peek().is_some_and(|l| l.kind.error == block.0) {
-                // Safety: the loop's check guarantees that `.next()` always returns `Some`.
-                let log = unsafe { iterator.next().unwrap_unchecked() };
-
-                block.1.push('\n');
-                block.1.push_str(&log.display(None));
-
-                if let Some(ref mut string) = block.2 {
-                    string.push('\n');
-                    string.push_str(&log.display(Some(log.stream())));
+        let mut blocks: Vec<(bool, String)> = vec![];
+        let mut records = vec![];
+
+        for log in self.queue.drain(..) {
+            if print && log.kind >= print_level {
+                let display = log.display(display_color.then(|| log.stream()));
+
+                // Consecutive logs with the same stream are printed together.
+                match blocks.last_mut() {
+                    Some((error, block)) if *error == log.kind.error => {
+                        block.push('\n');
+                        block.push_str(&display);
+                    }
+                    _ => blocks.push((log.kind.error, display)),
                 }
             }
 
-            blocks.push(block);
+            if write && log.kind >= write_level {
+                records.push(log.record(format));
+            }
         }
 
-        if self.config.print {
-            let mut out = None;
-            let mut err = None;
+        let mut out = None;
+        let mut err = None;
 
-            for (error, display) in blocks.iter().map(|(e, d, c)| (e, c.as_ref().unwrap_or(d))) {
-                if *error {
-                    writeln!(out.get_or_insert_with(|| std::io::stdout().lock()), "{display}")?;
-                } else {
-                    writeln!(err.get_or_insert_with(|| std::io::stderr().lock()), "{display}")?;
-                }
+        for (error, display) in blocks {
+            if error {
+                writeln!(out.get_or_insert_with(|| std::io::stdout().lock()), "{display}")?;
+            } else {
+                writeln!(err.get_or_insert_with(|| std::io::stderr().lock()), "{display}")?;
             }
         }
 
-        if self.config.write {
-            if let Some(dir) = self.path.parent() {
-                std::fs::create_dir_all(dir)?;
-            }
+        if !records.is_empty() {
+            let buffer = records.join("\n") + "\n";
+
+            self.rotate(buffer.len() as u64)?;
+
+            std::fs::create_dir_all(&self.dir)?;
 
             let mut file = File::options().append(true).create(true).open(&self.path)?;
-            let buffer = blocks.into_iter().map(|(_, d, _)| d).collect::<Box<_>>().join("\n");
 
-            writeln!(file, "{buffer}")?;
+            file.write_all(buffer.as_bytes())?;
+            self.size += buffer.len() as u64;
+        }
+
+        Ok(())
+    }
+
+    /// Starts a new output file if the current file should be rotated before the given number of
+    /// bytes are written into it, then tidies any previous log files.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if previous log files could not be tidied.
+    fn rotate(&mut self, bytes: u64) -> std::io::Result<()> {
+        let time = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+
+        if !self.config.rotation.should_rotate(self.started, time.date(), self.size, bytes) {
+            return Ok(());
+        }
+
+        self.path = Self::file_path(&self.dir, time, self.config.format);
+        self.started = time.date();
+        self.size = 0;
+
+        self.tidy()
+    }
+
+    /// Applies the configured [`Rotation`] policy to every log file within the output directory
+    /// other than the current output file, removing or compressing previous files.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a log file could not be removed or compressed.
+    pub fn tidy(&self) -> std::io::Result<()> {
+        if !self.config.write {
+            return Ok(());
+        }
+
+        crate::rotate::tidy(&self.dir, &self.path, &self.config.rotation)
+    }
+
+    /// Sets the minimum level of logs that are output to the given sink, flushing any logs that
+    /// were queued beforehand.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if log(s) failed to output during the flush.
+    pub fn set_level(&mut self, sink: Sink, level: Level) -> std::io::Result<()> {
+        if !self.is_empty() {
+            self.flush()?;
+        }
+
+        match sink {
+            Sink::Console => self.config.print_level = level,
+            Sink::File => self.config.write_level = level,
         }
 
         Ok(())
@@ -247,6 +377,14 @@ pub struct Config {
     pub queue_size: usize,
     /// The logger's output queue timeout in milliseconds.
     pub stale_time: u64,
+    /// The logger's file output format.
+    pub format: Format,
+    /// The minimum level of logs output to the console.
+    pub print_level: Level,
+    /// The minimum level of logs output to the log file.
+    pub write_level: Level,
+    /// The logger's file rotation and retention policy.
+    pub rotation: Rotation,
 }
 
 impl Config {
@@ -256,6 +394,12 @@ impl Config {
         !(self.print || self.write) || self.queue_size == 0
     }
 
+    /// Returns whether this [`Config`] outputs logs of the given level to any sink.
+    #[must_use]
+    pub fn accepts(&self, level: Level) -> bool {
+        (self.print && level >= self.print_level) || (self.write && level >= self.write_level)
+    }
+
     /// Returns whether this [`Config`] allows color support.
     #[must_use]
     pub const fn support_color(&self) -> bool {
@@ -263,6 +407,60 @@ impl Config {
     }
 }
 
+/// A log output destination.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Sink {
+    /// The console.
+    Console,
+    /// The log file.
+    File,
+}
+
+/// A log file output format.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum Format {
+    /// Human-readable text, with any structured fields appended as `logfmt` pairs.
+    #[default]
+    Text,
+    /// JSON Lines, where each log is written as a single JSON object.
+    Json,
+}
+
+impl Format {
+    /// A list of every log file format.
+    pub const LIST: &'static [Self] = &[Self::Text, Self::Json];
+
+    /// Returns the name of this format.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Json => "json",
+        }
+    }
+
+    /// Returns the file extension used for log files of this format.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Json => "jsonl",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::LIST
+            .iter()
+            .find(|f| f.name().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| format!("unknown log format '{s}'"))
+    }
+}
+
 /// A log entry.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Log {
@@ -272,12 +470,22 @@ pub struct Log {
     pub kind: Level,
     /// The log's text.
     pub text: Box<str>,
+    /// The log's structured fields.
+    pub fields: Box<[Field]>,
 }
 
 impl Log {
     /// Creates a new [`Log`].
     pub fn new(time: Time, kind: Level, text: impl Display) -> Self {
-        Self { time, kind, text: text.to_string().into_boxed_str() }
+        Self { time, kind, text: text.to_string().into_boxed_str(), fields: Box::new([]) }
+    }
+
+    /// Returns this [`Log`] with the given structured fields.
+    #[must_use]
+    pub fn with_fields(mut self, fields: impl Into<Box<[Field]>>) -> Self {
+        self.fields = fields.into();
+
+        self
     }
 
     /// Returns the preferred output stream of this [`Log`].
@@ -286,14 +494,58 @@ impl Log {
         if self.kind.error { Stream::Stderr } else { Stream::Stdout }
     }
 
-    /// Formats and returns a display string representing this log.
-    #[must_use]
-    pub fn display(&self, color_stream: Option<Stream>) -> String {
+    /// Formats and returns the timestamp, level, and text of this log.
+    fn header(&self, color_stream: Option<Stream>) -> String {
         let time = self.time.display(color_stream);
         let kind = self.kind.display(color_stream);
 
         format!("{time} {kind} {}", self.text)
     }
+
+    /// Formats and returns a display string representing this log.
+    #[must_use]
+    pub fn display(&self, color_stream: Option<Stream>) -> String {
+        let mut text = self.header(color_stream);
+
+        text.extend(self.fields.iter().map(|Field { key, value }| {
+            color_stream.map_or_else(
+                || format!(" {key}={value}"),
+                |s| format!(" {}={value}", key.if_supports_color(s, |k| k.dimmed())),
+            )
+        }));
+
+        text
+    }
+
+    /// Formats and returns a string representing this log within a log file of the given format.
+    ///
+    /// Text logs append any structured fields after a tab character as `logfmt` pairs. Because
+    /// values never contain raw tabs, fields can be recovered by splitting each line at its last
+    /// tab. JSON logs contain the log's `time`, `level`, `error`, `message`, and `fields` keys.
+    #[must_use]
+    pub fn record(&self, format: Format) -> String {
+        if format == Format::Json {
+            let fields = self.fields.iter().map(|f| (f.key.to_string(), f.value.to_json()));
+            let object = serde_json::json!({
+                "time": self.time.rfc3339(),
+                "level": self.kind.name,
+                "error": self.kind.error,
+                "message": &(*self.text),
+                "fields": fields.collect::<serde_json::Map<_, _>>(),
+            });
+
+            return object.to_string();
+        }
+
+        let mut text = self.header(None);
+
+        for (index, field) in self.fields.iter().enumerate() {
+            text.push(if index == 0 { '\t' } else { ' ' });
+            text.push_str(&field.to_string());
+        }
+
+        text
+    }
 }
 
 /// A log timestamp.
@@ -323,6 +575,13 @@ impl Time {
         Self::new(OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc()))
     }
 
+    /// Formats and returns this timestamp as an RFC 3339 string.
+    #[must_use]
+    pub fn rfc3339(&self) -> String {
+        // Formatting only fails for years that cannot be represented, which are never logged.
+        self.inner.format(&Rfc3339).unwrap_or_else(|_| self.display(None))
+    }
+
     /// Formats and returns a display string representing this timestamp.
     ///
     /// # Panics
@@ -342,10 +601,14 @@ impl Time {
 }
 
 /// A log level.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+///
+/// Levels are ordered and compared by their severity, then by their name.
+#[derive(Clone, Copy, Debug)]
 pub struct Level {
     /// The log level's name.
     pub name: &'static str,
+    /// The log level's severity, where higher values are more severe.
+    pub severity: u8,
     /// Whether the log level is considered an error.
     pub error: bool,
     /// Colorizes a string with the associated level color.
@@ -354,8 +617,13 @@ pub struct Level {
 
 impl Level {
     /// Creates a new [`Level`].
-    pub const fn new(name: &'static str, error: bool, color: fn(&String) -> String) -> Self {
-        Self { name, error, color }
+    pub const fn new(
+        name: &'static str,
+        severity: u8,
+        error: bool,
+        color: fn(&String) -> String,
+    ) -> Self {
+        Self { name, severity, error, color }
     }
 
     /// Formats and returns a display string representing this log level.
@@ -371,71 +639,268 @@ impl Level {
     }
 }
 
+impl PartialEq for Level {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Level {}
+
+impl PartialOrd for Level {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Level {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.severity.cmp(&other.severity).then_with(|| self.name.cmp(other.name))
+    }
+}
+
+impl Hash for Level {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.severity.hash(state);
+        self.name.hash(state);
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::LIST
+            .iter()
+            .find(|l| l.name.eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| format!("unknown log level '{s}'"))
+    }
+}
+
 /// Defines log levels.
 macro_rules! levels {
-    {$($const:ident($name:literal, $error:literal, $color:ident),)* $(,)?} => {
-        impl Level {$(
+    {$($const:ident($name:literal, $severity:literal, $error:literal, $color:ident),)* $(,)?} => {
+        impl Level {
+            /// A list of every logging level, ordered by severity.
+            pub const LIST: &'static [Self] = &[$(Self::$const),*];
+        $(
             #[doc = concat!("The ", $name, " logging level.")]
-            pub const $const: Self = Self::new($name, $error, |s| ::owo_colors::OwoColorize::$color(s).to_string());
+            pub const $const: Self = Self::new($name, $severity, $error, |s| ::owo_colors::OwoColorize::$color(s).to_string());
         )*}
     };
 }
 
 levels! {
-    DEBUG("debug", false, bright_purple),
-    INFO("info", false, bright_blue),
-    WARN("warn", false, bright_yellow),
-    ERROR("error", false, bright_red),
+    DEBUG("debug", 0, false, bright_purple),
+    INFO("info", 1, false, bright_blue),
+    WARN("warn", 2, false, bright_yellow),
+    ERROR("error", 3, false, bright_red),
+}
+
+/// Queues a log with the given level, splitting its format arguments from any structured fields.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __queue {
+    ($level:expr; [$($args:tt)+]) => {
+        $crate::queue($level, ::std::format_args!($($args)+))
+    };
+    ($level:expr; [$($args:tt)+]; $($fields:tt)*) => {
+        $crate::queue_with_fields($level, ::std::format_args!($($args)+), $crate::__fields!([] $($fields)*))
+    };
+    ($level:expr; [$($args:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::__queue!($level; [$($args)* $next] $($rest)*)
+    };
+}
+
+/// Creates an array of structured log fields.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fields {
+    ([$($field:expr),*] $(,)?) => {
+        [$($field),*]
+    };
+    ([$($field:expr),*] $key:ident = %$value:expr $(, $($rest:tt)*)?) => {
+        $crate::__fields!([$($field,)* $crate::Field::display(::std::stringify!($key), &$value)] $($($rest)*)?)
+    };
+    ([$($field:expr),*] $key:ident = ?$value:expr $(, $($rest:tt)*)?) => {
+        $crate::__fields!([$($field,)* $crate::Field::debug(::std::stringify!($key), &$value)] $($($rest)*)?)
+    };
+    ([$($field:expr),*] $key:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__fields!([$($field,)* $crate::Field::new(::std::stringify!($key), $value)] $($($rest)*)?)
+    };
+    ([$($field:expr),*] $key:ident $(, $($rest:tt)*)?) => {
+        $crate::__fields!([$($field),*] $key = $key $(, $($rest)*)?)
+    };
 }
 
 /// Outputs a debug log.
 ///
-/// ```
+/// Structured fields may be given after a semicolon, where `%` and `?` prefixes record a value's
+/// [`Display`] or [`Debug`](<std::fmt::Debug>) text respectively, and a lone variable name is
+/// recorded under its own name.
+///
+/// The logging thread must have been initialized before logging.
+///
+/// ```no_run
+/// use doop_logger::debug;
+/// # let (path, user) = ("log", "user");
+///
 /// debug!("This is an debug log!")?;
+/// debug!("This is an debug log with fields!"; count = 2, path = ?path, user)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 #[macro_export]
 macro_rules! debug {
     ($($args:tt)+) => {
-        if ::std::cfg!(debug_assertions) {
-            $crate::queue($crate::Level::DEBUG, ::std::format_args!($($args)+))
-        } else {
-            ::std::result::Result::<(), ::std::sync::mpsc::SendError<$crate::Message>>::Ok(())
-        }
+        $crate::__queue!($crate::Level::DEBUG; [] $($args)+)
     };
 }
 
 /// Outputs an info log.
 ///
-/// ```
+/// Structured fields may be given after a semicolon.
+///
+/// ```no_run
+/// use doop_logger::info;
+/// # let guild_id = 0;
+///
 /// info!("This is an info log!")?;
+/// info!("This is an info log with fields!"; guild = %guild_id)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 #[macro_export]
 macro_rules! info {
     ($($args:tt)+) => {
-        $crate::queue($crate::Level::INFO, ::std::format_args!($($args)+))
+        $crate::__queue!($crate::Level::INFO; [] $($args)+)
     };
 }
 
 /// Outputs a warn log.
 ///
-/// ```
+/// Structured fields may be given after a semicolon.
+///
+/// ```no_run
+/// use doop_logger::warn;
+///
 /// warn!("This is a warning log!")?;
+/// warn!("This is a warning log with fields!"; retries = 3)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 #[macro_export]
 macro_rules! warn {
     ($($args:tt)+) => {
-        $crate::queue($crate::Level::WARN, ::std::format_args!($($args)+))
+        $crate::__queue!($crate::Level::WARN; [] $($args)+)
     };
 }
 
 /// Outputs an error log.
 ///
-/// ```
+/// Structured fields may be given after a semicolon.
+///
+/// ```no_run
+/// use doop_logger::error;
+/// # let error = std::io::Error::other("example");
+///
 /// error!("This is an error log!")?;
+/// error!("This is an error log with fields!"; error = %error)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 #[macro_export]
 macro_rules! error {
     ($($args:tt)+) => {
-        $crate::queue($crate::Level::ERROR, ::std::format_args!($($args)+))
+        $crate::__queue!($crate::Level::ERROR; [] $($args)+)
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use crate::{Config, Field, Format, Level, Log, Logger, Rotation, Sink, Time};
+
+    /// A configuration that prints debug logs and writes info logs.
+    const CONFIG: Config = Config {
+        print: true,
+        write: true,
+        color: false,
+        queue_size: 8,
+        stale_time: 1000,
+        format: Format::Text,
+        print_level: Level::DEBUG,
+        write_level: Level::INFO,
+        rotation: Rotation::NEVER,
+    };
+
+    /// Returns a log with the given structured fields at a fixed time.
+    fn log(fields: impl Into<Box<[Field]>>) -> Log {
+        let time = Time::new(datetime!(2024-01-02 03:04:05 UTC));
+
+        Log::new(time, Level::WARN, "message").with_fields(fields)
+    }
+
+    #[test]
+    fn parse_formats() {
+        assert_eq!("json".parse(), Ok(Format::Json));
+        assert_eq!("TEXT".parse(), Ok(Format::Text));
+        assert!("yaml".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn json_records() -> serde_json::Result<()> {
+        let fields = [Field::new("guild", 1_u64), Field::new("user", "a b")];
+        let record = log(fields).record(Format::Json);
+
+        // Each record must fit on a single line.
+        assert!(!record.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&record)?,
+            serde_json::json!({
+                "time": "2024-01-02T03:04:05Z",
+                "level": "warn",
+                "error": false,
+                "message": "message",
+                "fields": { "guild": 1, "user": "a b" },
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn text_records() {
+        let fields = [Field::new("guild", 1_u64), Field::new("user", "a b")];
+
+        assert_eq!(log([]).record(Format::Text), "[02-01-24 03:04:05.000000] (warn) message");
+        assert_eq!(
+            log(fields).record(Format::Text),
+            "[02-01-24 03:04:05.000000] (warn) message\tguild=1 user=\"a b\""
+        );
+    }
+
+    #[test]
+    fn levels_are_ordered() {
+        assert!(Level::LIST.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!("WARN".parse(), Ok(Level::WARN));
+        assert!("trace".parse::<Level>().is_err());
+    }
+
+    #[test]
+    fn sinks_filter_levels() {
+        assert!(CONFIG.accepts(Level::DEBUG));
+        assert!(!Config { print: false, ..CONFIG }.accepts(Level::DEBUG));
+        assert!(Config { print: false, ..CONFIG }.accepts(Level::INFO));
+        assert!(!Config { write: false, print_level: Level::ERROR, ..CONFIG }.accepts(Level::WARN));
+
+        // Logs that no sink accepts are never queued.
+        let mut logger = Logger::new(Config { print: false, ..CONFIG }, "log");
+
+        assert!(logger.queue(Log::new(Time::now(), Level::DEBUG, "message")).is_ok());
+        assert!(logger.is_empty());
+
+        // Levels may be lowered at runtime.
+        assert!(logger.set_level(Sink::File, Level::DEBUG).is_ok());
+        assert!(logger.queue(Log::new(Time::now(), Level::DEBUG, "message")).is_ok());
+        assert!(!logger.is_empty());
+    }
+}
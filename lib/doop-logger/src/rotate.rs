@@ -0,0 +1,245 @@
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use time::Date;
+
+/// The file extension appended to compressed log files.
+pub const COMPRESSED_EXTENSION: &str = "gz";
+
+/// The number of seconds within a day.
+const DAY_SECONDS: u64 = 24 * 60 * 60;
+
+/// A log file rotation and retention policy.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Rotation {
+    /// The maximum size of a log file in bytes before a new file is started, if limited.
+    pub max_size: Option<u64>,
+    /// Whether a new log file is started whenever the local date changes.
+    pub daily: bool,
+    /// Whether previous log files are compressed using gzip.
+    pub compress: bool,
+    /// The number of days that log files are retained for since they were last modified, if
+    /// limited.
+    pub max_age: Option<u64>,
+    /// The maximum number of retained log files, including the current file, if limited.
+    pub max_files: Option<usize>,
+}
+
+impl Rotation {
+    /// A policy that never rotates, compresses, or removes log files.
+    pub const NEVER: Self =
+        Self { max_size: None, daily: false, compress: false, max_age: None, max_files: None };
+
+    /// Returns whether a log file that was started on the given date and has the given size
+    /// should be rotated before the given number of bytes are written into it on the given date.
+    ///
+    /// Empty files are never rotated by size, so logs larger than the maximum size are still
+    /// written.
+    #[must_use]
+    pub fn should_rotate(&self, started: Date, today: Date, size: u64, bytes: u64) -> bool {
+        let oversized =
+            self.max_size.is_some_and(|max| size > 0 && size.saturating_add(bytes) > max);
+
+        oversized || (self.daily && started != today)
+    }
+}
+
+/// Returns whether the given file name belongs to a log file.
+fn is_log_file(name: &str) -> bool {
+    let name = name.strip_suffix(&format!(".{COMPRESSED_EXTENSION}")).unwrap_or(name);
+    let Some((stem, extension)) = name.rsplit_once('.') else {
+        return false;
+    };
+    let valid_stem = stem.len() == 19
+        && stem
+            .bytes()
+            .enumerate()
+            .all(|(i, b)| if i == 6 { b == b'-' } else { b.is_ascii_digit() });
+
+    valid_stem && matches!(extension, "txt" | "jsonl")
+}
+
+/// Compresses the log file at the given path using gzip, replacing it with a file of the same name
+/// with an added `.gz` extension.
+///
+/// The file is streamed into a hidden temporary file that is only renamed into place once it has
+/// been fully written, so an interrupted compression never leaves behind a truncated archive.
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be read, compressed, or removed.
+fn compress(path: &Path) -> std::io::Result<()> {
+    let mut target = path.as_os_str().to_owned();
+
+    target.push(format!(".{COMPRESSED_EXTENSION}"));
+
+    let target = PathBuf::from(target);
+    let mut temporary = OsString::from(".");
+
+    temporary.push(target.file_name().unwrap_or_default());
+    temporary.push(".tmp");
+
+    let temporary = target.with_file_name(temporary);
+    let mut encoder = GzEncoder::new(File::create(&temporary)?, Compression::default());
+
+    std::io::copy(&mut BufReader::new(File::open(path)?), &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+
+    std::fs::rename(temporary, target)?;
+    std::fs::remove_file(path)
+}
+
+/// Applies the given policy to every log file within the given directory other than the current
+/// file, removing files that should not be retained and compressing the rest if enabled.
+///
+/// # Errors
+///
+/// This function will return an error if the directory could not be read, or if a log file could
+/// not be removed or compressed.
+pub fn tidy(dir: &Path, current: &Path, rotation: &Rotation) -> std::io::Result<()> {
+    if !rotation.compress && rotation.max_age.is_none() && rotation.max_files.is_none() {
+        return Ok(());
+    }
+
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error),
+    };
+    let mut files = vec![];
+
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+
+        if &(*path) == current || !self::is_log_file(name) || !entry.file_type()?.is_file() {
+            continue;
+        }
+
+        files.push((name.to_string(), entry.metadata()?.modified()?, path));
+    }
+
+    // File names begin with their creation time, so this sorts files from newest to oldest.
+    files.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+
+    let now = SystemTime::now();
+    let max_age =
+        rotation.max_age.map(|days| Duration::from_secs(days.saturating_mul(DAY_SECONDS)));
+    // The current file counts towards the maximum number of files.
+    let kept = rotation.max_files.map(|count| count.saturating_sub(1));
+
+    for (index, (_, modified, path)) in files.into_iter().enumerate() {
+        let expired =
+            max_age.is_some_and(|age| now.duration_since(modified).is_ok_and(|d| d > age));
+        let compressed = path.extension().is_some_and(|e| e == COMPRESSED_EXTENSION);
+
+        if expired || kept.is_some_and(|kept| index >= kept) {
+            std::fs::remove_file(path)?;
+        } else if rotation.compress && !compressed {
+            self::compress(&path)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    use time::macros::date;
+
+    use super::{Rotation, COMPRESSED_EXTENSION};
+
+    #[test]
+    fn rotate_by_size_and_day() {
+        let rotation = Rotation { max_size: Some(10), daily: true, ..Rotation::NEVER };
+        let today = date!(2024 - 01 - 02);
+
+        assert!(!rotation.should_rotate(today, today, 5, 5));
+        assert!(rotation.should_rotate(today, today, 5, 6));
+        // Empty files are never rotated by size.
+        assert!(!rotation.should_rotate(today, today, 0, 20));
+        assert!(rotation.should_rotate(date!(2024 - 01 - 01), today, 0, 0));
+        assert!(!Rotation::NEVER.should_rotate(date!(2024 - 01 - 01), today, 20, 20));
+    }
+
+    #[test]
+    fn detect_log_files() {
+        assert!(super::is_log_file("240102-030405000000.txt"));
+        assert!(super::is_log_file("240102-030405000000.jsonl"));
+        assert!(super::is_log_file(&format!("240102-030405000000.txt.{COMPRESSED_EXTENSION}")));
+        assert!(!super::is_log_file("240102-030405000000.json"));
+        assert!(!super::is_log_file("notes.txt"));
+        assert!(!super::is_log_file(".240102-030405000000.txt.gz.tmp"));
+    }
+
+    #[test]
+    fn retain_and_compress() -> std::io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("doop-logger-rotate-{}", std::process::id()));
+        let names =
+            ["240101-000000000000.txt", "240102-000000000000.txt", "240103-000000000000.txt"];
+
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join("notes.txt"), "")?;
+
+        for name in names {
+            std::fs::write(dir.join(name), "log")?;
+        }
+
+        let current = dir.join("240104-000000000000.txt");
+        let rotation = Rotation { compress: true, max_files: Some(3), ..Rotation::NEVER };
+        let result = super::tidy(&dir, &current, &rotation);
+        let exists = |name: &str| Path::exists(&dir.join(name));
+
+        // The current file counts towards the maximum, so only the two newest files are kept.
+        let kept = (
+            !exists(names[0]),
+            exists(&format!("{}.{COMPRESSED_EXTENSION}", names[1])) && !exists(names[1]),
+            exists(&format!("{}.{COMPRESSED_EXTENSION}", names[2])) && !exists(names[2]),
+            exists("notes.txt"),
+        );
+
+        std::fs::remove_dir_all(&dir)?;
+        result?;
+
+        assert_eq!(kept, (true, true, true, true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn remove_expired_files() -> std::io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("doop-logger-expire-{}", std::process::id()));
+        let old = dir.join("240101-000000000000.jsonl");
+        let new = dir.join("240102-000000000000.txt");
+
+        std::fs::create_dir_all(&dir)?;
+
+        let modified = SystemTime::now() - Duration::from_secs(2 * super::DAY_SECONDS);
+
+        File::create(&old)?.set_modified(modified)?;
+        File::create(&new)?;
+
+        let rotation = Rotation { max_age: Some(1), ..Rotation::NEVER };
+        let result = super::tidy(&dir, &dir.join("240103-000000000000.txt"), &rotation);
+        let kept = (old.exists(), new.exists());
+
+        std::fs::remove_dir_all(&dir)?;
+        result?;
+
+        assert_eq!(kept, (false, true));
+
+        Ok(())
+    }
+}
@@ -9,19 +9,62 @@ mod storage;
 
 /// Derives the [`Storage`](<doop_storage::Storage>) trait for the deriving type.
 ///
+/// Every location argument type must implement both [`Display`](<std::fmt::Display>) and
+/// [`FromStr`](<std::str::FromStr>), allowing stored locations to be listed and parsed. The location
+/// must contain exactly one `{}` placeholder per argument type, and each formatted argument is
+/// [escaped](<doop_storage::Location::escape>) so that it always remains a single path component.
+///
+/// A type may optionally be versioned using the `version` attribute, alongside a `migrations`
+/// attribute listing one [`Migration`](<doop_storage::Migration>) function for each previous
+/// version. Values stored with an older version are upgraded step by step when read. Versioned
+/// types must use a self-describing format, which excludes formats such as
+/// [`Bincode`](<doop_storage::Bincode>).
+///
+/// A non-generic type may also be cached using the `cache` attribute, which shares a bounded
+/// [`Cache`](<doop_storage::Cache>) with the given capacity between every key of the type. The
+/// cache is only used while a value is watched by a [`Watcher`](<doop_storage::Watcher>), or once
+/// it has been written within the current process.
+///
+/// The `index` attribute lists one or more fields, such as `status.kind`, that are maintained as
+/// secondary [`Index`](<doop_storage::Index>) entries whenever a value is written or removed. Each
+/// index is named after its field, and may be queried using
+/// [`Stored::query`](<doop_storage::Stored::query>). This also requires a non-generic type.
+///
+/// The `namespace` attribute selects the location argument, by index, that every value is
+/// accounted within when enforcing storage [`Quota`](<doop_storage::Quota>) limits, such as a
+/// guild identifier.
+///
 /// # Examples
 ///
 /// ```
 /// #[derive(Storage, Serialize, Deserialize)]
 /// #[format(Compress<MsgPack, 3>)]
 /// #[location("{}/{}", u64, String)]
+/// #[namespace(0)]
 /// struct Data {
 ///     id: Id<GuildMarker>,
 ///     time: OffsetDateTime,
 /// }
+///
+/// #[derive(Clone, Storage, Serialize, Deserialize)]
+/// #[format(Json)]
+/// #[location("{}", u64)]
+/// #[version(1)]
+/// #[cache(64)]
+/// #[index(name)]
+/// #[migrations(self::add_name)]
+/// struct Named {
+///     id: u64,
+///     name: String,
+/// }
+///
+/// fn add_name(mut value: Value) -> Value {
+///     value["name"] = Value::from("unnamed");
+///     value
+/// }
 /// ```
 #[inline]
-#[proc_macro_derive(Storage, attributes(format, location))]
+#[proc_macro_derive(Storage, attributes(cache, format, index, location, migrations, namespace, version))]
 pub fn storage(input: TokenStream) -> TokenStream {
     crate::storage::procedure(input)
 }
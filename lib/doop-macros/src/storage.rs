@@ -1,7 +1,12 @@
 use proc_macro::TokenStream;
+use proc_macro2::{Ident, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
 use syn::parse::ParseStream;
-use syn::{parse_macro_input, Attribute, DeriveInput, Error, LitStr, Result, Token, Type};
+use syn::punctuated::Punctuated;
+use syn::{
+    parse_macro_input, Attribute, DeriveInput, Error, Generics, LitInt, LitStr, Member, Path,
+    Result, Token, Type,
+};
 
 struct FormatAttribute(Type);
 
@@ -24,10 +29,200 @@ fn parse_location_attribute(attribute: &Attribute) -> Result<LocationAttribute>
             }
         }
 
+        let placeholders = self::count_placeholders(&literal)?;
+
+        if placeholders != arguments.len() {
+            let message = format!(
+                "the location has {placeholders} placeholder(s), but {} argument type(s) were \
+                 given",
+                arguments.len()
+            );
+
+            return Err(Error::new_spanned(literal, message));
+        }
+
         Ok(LocationAttribute(literal, arguments))
     })
 }
 
+fn count_placeholders(literal: &LitStr) -> Result<usize> {
+    let template = literal.value();
+    let mut chars = template.chars().peekable();
+    let mut count = 0;
+
+    while let Some(char) = chars.next() {
+        match char {
+            '{' | '}' if chars.peek() == Some(&char) => {
+                chars.next();
+            }
+            // Formatting specifiers would be applied to escaped arguments, so they are rejected.
+            '{' if chars.next() == Some('}') => count += 1,
+            '{' | '}' => {
+                return Err(Error::new_spanned(
+                    literal,
+                    "location placeholders must be written as `{}`",
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(count)
+}
+
+struct VersionAttribute(LitInt);
+
+fn parse_version_attribute(attribute: &Attribute) -> Result<VersionAttribute> {
+    attribute.parse_args_with(|input: ParseStream| Ok(VersionAttribute(input.parse()?)))
+}
+
+struct CacheAttribute(LitInt);
+
+fn parse_cache_attribute(attribute: &Attribute) -> Result<CacheAttribute> {
+    attribute.parse_args_with(|input: ParseStream| Ok(CacheAttribute(input.parse()?)))
+}
+
+fn cache_function(
+    attribute: &Attribute,
+    ident: &Ident,
+    generics: &Generics,
+) -> Result<TokenStream2> {
+    let CacheAttribute(capacity) = self::parse_cache_attribute(attribute)?;
+
+    // Statics cannot depend on generic parameters, so generic types cannot share a cache.
+    if !generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            attribute,
+            "the `cache` attribute requires a non-generic type",
+        ));
+    }
+
+    Ok(quote! {
+        fn cache() -> ::std::option::Option<::std::sync::Arc<::doop_storage::Cache<Self>>> {
+            static CACHE: ::std::sync::OnceLock<::std::sync::Arc<::doop_storage::Cache<#ident>>> =
+                ::std::sync::OnceLock::new();
+
+            let cache = CACHE.get_or_init(|| ::std::sync::Arc::new(::doop_storage::Cache::new(#capacity)));
+
+            ::std::option::Option::Some(::std::sync::Arc::clone(cache))
+        }
+    })
+}
+
+struct IndexAttribute(Vec<Punctuated<Member, Token![.]>>);
+
+fn parse_index_attribute(attribute: &Attribute) -> Result<IndexAttribute> {
+    attribute.parse_args_with(|input: ParseStream| {
+        let mut fields = vec![];
+
+        while !input.is_empty() {
+            fields.push(Punctuated::<Member, Token![.]>::parse_separated_nonempty(input)?);
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(IndexAttribute(fields))
+    })
+}
+
+fn index_function(
+    attributes: &[&Attribute],
+    ident: &Ident,
+    generics: &Generics,
+    location: &LitStr,
+) -> Result<TokenStream2> {
+    let mut indexes = vec![];
+
+    for attribute in attributes {
+        let IndexAttribute(fields) = self::parse_index_attribute(attribute)?;
+
+        // Statics cannot depend on generic parameters, so generic types cannot share indexes.
+        if !generics.params.is_empty() {
+            return Err(Error::new_spanned(
+                attribute,
+                "the `index` attribute requires a non-generic type",
+            ));
+        }
+
+        for field in fields {
+            let name = field.iter().map(|m| quote!(#m).to_string()).collect::<Vec<_>>().join(".");
+
+            indexes.push(quote! {
+                ::doop_storage::Index::new(#location, #name, |value: &#ident| {
+                    ::doop_storage::serde_json::to_string(&value.#field)
+                })
+            });
+        }
+    }
+
+    Ok(quote! {
+        fn indexes() -> ::std::sync::Arc<[::doop_storage::Index<Self>]> {
+            static INDEXES: ::std::sync::OnceLock<::std::sync::Arc<[::doop_storage::Index<#ident>]>> =
+                ::std::sync::OnceLock::new();
+
+            let indexes = INDEXES.get_or_init(|| ::std::sync::Arc::from([#(#indexes),*]));
+
+            ::std::sync::Arc::clone(indexes)
+        }
+    })
+}
+
+struct NamespaceAttribute(LitInt);
+
+fn parse_namespace_attribute(attribute: &Attribute) -> Result<NamespaceAttribute> {
+    attribute.parse_args_with(|input: ParseStream| Ok(NamespaceAttribute(input.parse()?)))
+}
+
+fn with_namespace(attribute: &Attribute, fmt_args: &[Ident]) -> Result<TokenStream2> {
+    let NamespaceAttribute(index) = self::parse_namespace_attribute(attribute)?;
+    let Some(argument) = fmt_args.get(index.base10_parse::<usize>()?) else {
+        return Err(Error::new_spanned(index, "the namespace must be a location argument index"));
+    };
+
+    Ok(quote! { .with_namespace(#argument.as_str()) })
+}
+
+struct MigrationsAttribute(Vec<Path>);
+
+fn parse_migrations_attribute(attribute: &Attribute) -> Result<MigrationsAttribute> {
+    attribute.parse_args_with(|input: ParseStream| {
+        let paths = Punctuated::<Path, Token![,]>::parse_terminated(input)?;
+
+        Ok(MigrationsAttribute(paths.into_iter().collect()))
+    })
+}
+
+fn schema(
+    version: Option<LitInt>,
+    migrations: Option<(&Attribute, Vec<Path>)>,
+) -> Result<Option<TokenStream2>> {
+    let (version, migrations) = match (version, migrations) {
+        (None, None) => return Ok(None),
+        (None, Some((attribute, _))) => {
+            return Err(Error::new_spanned(
+                attribute,
+                "the `version` attribute must be configured",
+            ));
+        }
+        (Some(version), migrations) => (version, migrations.map(|(_, m)| m).unwrap_or_default()),
+    };
+    let count = version.base10_parse::<u32>()?;
+
+    if usize::try_from(count).ok() != Some(migrations.len()) {
+        let message = format!("expected {count} migration(s), found {}", migrations.len());
+
+        return Err(Error::new(version.span(), message));
+    }
+
+    Ok(Some(quote! {{
+        const MIGRATIONS: &[::doop_storage::Migration] = &[#(#migrations),*];
+
+        ::doop_storage::Schema::new(#version, MIGRATIONS)
+    }}))
+}
+
 pub fn procedure(input: TokenStream) -> TokenStream {
     let DeriveInput { attrs, ident, generics, .. } = parse_macro_input!(input as DeriveInput);
 
@@ -51,18 +246,125 @@ pub fn procedure(input: TokenStream) -> TokenStream {
         Err(error) => return error.into_compile_error().into(),
     };
 
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let version = match attrs.iter().find(|a| a.path().is_ident("version")) {
+        Some(attribute) => match self::parse_version_attribute(attribute) {
+            Ok(VersionAttribute(version)) => Some(version),
+            Err(error) => return error.into_compile_error().into(),
+        },
+        None => None,
+    };
+    let migrations = match attrs.iter().find(|a| a.path().is_ident("migrations")) {
+        Some(attribute) => match self::parse_migrations_attribute(attribute) {
+            Ok(MigrationsAttribute(migrations)) => Some((attribute, migrations)),
+            Err(error) => return error.into_compile_error().into(),
+        },
+        None => None,
+    };
+
+    let cache = match attrs.iter().find(|a| a.path().is_ident("cache")) {
+        Some(attribute) => match self::cache_function(attribute, &ident, &generics) {
+            Ok(function) => Some(function),
+            Err(error) => return error.into_compile_error().into(),
+        },
+        None => None,
+    };
+
+    let indexes = attrs.iter().filter(|a| a.path().is_ident("index")).collect::<Vec<_>>();
+    let indexes = match &(*indexes) {
+        [] => None,
+        indexes => match self::index_function(indexes, &ident, &generics, &location) {
+            Ok(function) => Some(function),
+            Err(error) => return error.into_compile_error().into(),
+        },
+    };
+
     let fmt_args = (0 .. args.len()).map(|n| format_ident!("_{n}")).collect::<Vec<_>>();
+    let schema = match self::schema(version, migrations) {
+        Ok(schema) => schema.map(|schema| quote! { .with_schema(#schema) }),
+        Err(error) => return error.into_compile_error().into(),
+    };
+    let with_namespace = match attrs.iter().find(|a| a.path().is_ident("namespace")) {
+        Some(attribute) => match self::with_namespace(attribute, &fmt_args) {
+            Ok(namespace) => Some(namespace),
+            Err(error) => return error.into_compile_error().into(),
+        },
+        None => None,
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let with_indexes = indexes.as_ref().map(|_| {
+        quote! { .with_indexes(<Self as ::doop_storage::Stored>::indexes()) }
+    });
+    let with_cache = cache.as_ref().map(|_| {
+        quote! {
+            let key = match <Self as ::doop_storage::Stored>::cache() {
+                ::std::option::Option::Some(cache) => key.with_cache(cache),
+                ::std::option::Option::None => key,
+            };
+        }
+    });
 
     quote! {
         impl #impl_generics ::doop_storage::Stored for #ident #ty_generics #where_clause {
             type Arguments = (#(#args),*);
             type Format = #format;
 
+            const LOCATION: &'static str = #location;
+
             fn stored((#(#fmt_args),*): Self::Arguments)-> ::doop_storage::Key<Self, Self::Format> {
-                format!(#location, #(#fmt_args),*).into()
+                let (#(#fmt_args),*) = (#(::doop_storage::Location::escape(&#fmt_args.to_string()).into_owned()),*);
+                let key = ::doop_storage::Key::from(format!(#location, #(#fmt_args),*))#schema #with_indexes #with_namespace;
+
+                #with_cache
+
+                key
+            }
+
+            #cache
+
+            #indexes
+
+            fn arguments(components: &[&str]) -> ::std::option::Option<Self::Arguments> {
+                let &[#(#fmt_args),*] = components else {
+                    return ::std::option::Option::None;
+                };
+
+                let (#(#fmt_args),*) = (#(::doop_storage::Location::unescape(#fmt_args)?.parse::<#args>().ok()?),*);
+
+                ::std::option::Option::Some((#(#fmt_args),*))
             }
         }
     }
     .into()
 }
+
+#[cfg(test)]
+mod tests {
+    use syn::{parse_quote, Attribute};
+
+    #[test]
+    fn location_placeholders_match_arguments() {
+        let attribute: Attribute = parse_quote!(#[location("role/{}/{}", u64, String)]);
+
+        assert!(super::parse_location_attribute(&attribute).is_ok());
+    }
+
+    #[test]
+    fn location_placeholder_count_mismatch() {
+        let attribute: Attribute = parse_quote!(#[location("role/{}/{}", u64)]);
+        let error = super::parse_location_attribute(&attribute).err().map(|e| e.to_string());
+
+        assert_eq!(
+            error.as_deref(),
+            Some("the location has 2 placeholder(s), but 1 argument type(s) were given")
+        );
+    }
+
+    #[test]
+    fn location_rejects_format_specifiers() {
+        let attribute: Attribute = parse_quote!(#[location("role/{:?}", u64)]);
+
+        assert!(super::parse_location_attribute(&attribute).is_err());
+    }
+}
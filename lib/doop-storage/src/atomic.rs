@@ -0,0 +1,187 @@
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The file extension used for temporary files.
+pub const TEMPORARY_EXTENSION: &str = "tmp";
+
+/// A process-local counter used to keep temporary file names unique.
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Returns a unique temporary sibling path for the given file path.
+///
+/// Temporary files are hidden and are named `.{file}.{pid}-{n}.tmp`, ensuring that concurrent
+/// writers never share a temporary file.
+fn temporary_path(path: &Path) -> PathBuf {
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let mut name = OsString::from(".");
+
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".{}-{count}.{TEMPORARY_EXTENSION}", std::process::id()));
+
+    path.with_file_name(name)
+}
+
+/// Returns whether the given path is a temporary file created by [`write`].
+pub fn is_temporary(path: &Path) -> bool {
+    let hidden = path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.'));
+
+    hidden && path.extension().is_some_and(|e| e == TEMPORARY_EXTENSION)
+}
+
+/// Synchronizes the given directory, ensuring that any renamed entries are persisted.
+///
+/// # Errors
+///
+/// This function will return an error if the directory could not be synchronized.
+#[cfg(unix)]
+fn sync_directory(dir: &Path) -> std::io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+/// Synchronizes the given directory, ensuring that any renamed entries are persisted.
+///
+/// This is a no-op on platforms that do not support opening directories.
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+const fn sync_directory(_: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Writes the given bytes into a new temporary sibling of the given path, synchronizing it to disk
+/// and returning the temporary file's path.
+///
+/// The returned file must be either [committed](<commit>) or [discarded](<discard>).
+///
+/// # Errors
+///
+/// This function will return an error if the temporary file could not be written.
+pub fn stage(path: &Path, bytes: &[u8]) -> std::io::Result<PathBuf> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let temporary = self::temporary_path(path);
+    let result = File::create(&temporary).and_then(|mut file| {
+        file.write_all(bytes)?;
+        file.sync_all()
+    });
+
+    if let Err(error) = result {
+        self::discard(&temporary);
+
+        return Err(error);
+    }
+
+    Ok(temporary)
+}
+
+/// Renames the given staged temporary file into place.
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be renamed.
+pub fn commit(temporary: &Path, path: &Path) -> std::io::Result<()> {
+    std::fs::rename(temporary, path)?;
+
+    path.parent().map_or(Ok(()), self::sync_directory)
+}
+
+/// Removes the given staged temporary file.
+pub fn discard(temporary: &Path) {
+    // The temporary file may not exist, so this is allowed to fail.
+    std::fs::remove_file(temporary).ok();
+}
+
+/// Atomically writes the given bytes into the file at the given path.
+///
+/// The bytes are first written into a temporary sibling file, which is synchronized to disk and
+/// then renamed into place. If this process is interrupted, the original file is left untouched.
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be written.
+pub fn write(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let temporary = self::stage(path, bytes)?;
+
+    self::commit(&temporary, path).inspect_err(|_| self::discard(&temporary))
+}
+
+/// Atomically writes each of the given files, where each entry contains a file's path, its new
+/// contents, and its previous contents.
+///
+/// Every file is staged before any are renamed into place. If renaming a file fails, all files that
+/// were already replaced are restored to their previous contents.
+///
+/// # Errors
+///
+/// This function will return an error if any file could not be written.
+pub fn write_all(files: &[(&Path, &[u8], &[u8])]) -> std::io::Result<()> {
+    let mut staged = Vec::with_capacity(files.len());
+
+    for (path, bytes, _) in files {
+        match self::stage(path, bytes) {
+            Ok(temporary) => staged.push(temporary),
+            Err(error) => {
+                for temporary in &staged {
+                    self::discard(temporary);
+                }
+
+                return Err(error);
+            }
+        }
+    }
+
+    for (index, temporary) in staged.iter().enumerate() {
+        if let Err(error) = self::commit(temporary, files[index].0) {
+            staged[index ..].iter().for_each(|t| self::discard(t));
+
+            // Restoring is best-effort, as the original error is more relevant to the caller.
+            for (path, _, previous) in &files[.. index] {
+                self::write(path, previous).ok();
+            }
+
+            return Err(error);
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes any temporary files left behind by interrupted writes within the given directory.
+///
+/// Returns the paths of all removed files.
+///
+/// # Errors
+///
+/// This function will return an error if the directory could not be traversed or a file could
+/// not be removed.
+pub fn recover(dir: &Path) -> std::io::Result<Box<[Box<Path>]>> {
+    let mut removed = vec![];
+    let mut queue = vec![dir.to_path_buf()];
+
+    while let Some(dir) = queue.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            // The storage directory is only created on the first write.
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(error) => return Err(error),
+        };
+
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+
+            if entry.file_type()?.is_dir() {
+                queue.push(path);
+            } else if self::is_temporary(&path) {
+                std::fs::remove_file(&path)?;
+                removed.push(path.into_boxed_path());
+            }
+        }
+    }
+
+    Ok(removed.into_boxed_slice())
+}
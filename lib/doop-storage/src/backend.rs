@@ -0,0 +1,600 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+
+use crate::lock::{Lock, LockSet};
+use crate::Quota;
+
+/// Returns the location string of the given relative path, such as `role/1/2.json`.
+///
+/// # Errors
+///
+/// This function will return an error if the path is not relative or is not valid UTF-8.
+pub fn location(path: &Path) -> std::io::Result<String> {
+    let mut location = String::new();
+
+    for component in path.components() {
+        let Component::Normal(component) = component else {
+            return Err(std::io::Error::new(ErrorKind::InvalidInput, "expected a relative path"));
+        };
+        let Some(component) = component.to_str() else {
+            return Err(std::io::Error::new(ErrorKind::InvalidInput, "expected a UTF-8 path"));
+        };
+
+        if !location.is_empty() {
+            location.push('/');
+        }
+
+        location.push_str(component);
+    }
+
+    Ok(location)
+}
+
+/// Returns the relative path of the given location string.
+///
+/// # Errors
+///
+/// This function will return an error if the location contains an empty, current, or parent
+/// directory component, which could otherwise be used to escape the storage root.
+pub fn from_location(location: &str) -> std::io::Result<PathBuf> {
+    let mut path = PathBuf::new();
+
+    for component in location.split('/') {
+        let mut components = Path::new(component).components();
+
+        let (Some(Component::Normal(component)), None) = (components.next(), components.next())
+        else {
+            return Err(std::io::Error::new(ErrorKind::InvalidInput, "invalid location"));
+        };
+
+        path.push(component);
+    }
+
+    Ok(path)
+}
+
+/// Returns the relative path of every resource within the given backend.
+///
+/// # Errors
+///
+/// This function will return an error if the backend could not be traversed.
+pub fn files(backend: &dyn Backend) -> std::io::Result<Vec<Box<Path>>> {
+    let mut files = vec![];
+    let mut queue = vec![PathBuf::new()];
+
+    while let Some(dir) = queue.pop() {
+        for Entry { path, is_dir } in backend.entries(&dir)? {
+            if is_dir {
+                queue.push(path.into_path_buf());
+            } else {
+                files.push(path);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// A single entry within a storage directory.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Entry {
+    /// The entry's path, relative to the storage root.
+    pub path: Box<Path>,
+    /// Whether the entry is a directory.
+    pub is_dir: bool,
+}
+
+/// A storage backend that resources are read from and written into.
+///
+/// All paths given to a backend are relative to the backend's storage root.
+pub trait Backend: Debug + Send + Sync {
+    /// Returns whether a resource exists at the given path.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the path could not be verified.
+    fn exists(&self, path: &Path) -> std::io::Result<bool>;
+
+    /// Reads the resource at the given path.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the resource does not exist or could not be read.
+    fn read(&self, path: &Path) -> std::io::Result<Vec<u8>>;
+
+    /// Atomically writes the given bytes into the resource at the given path.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the resource could not be written.
+    fn write(&self, path: &Path, bytes: &[u8]) -> std::io::Result<()>;
+
+    /// Writes each of the given resources, where each entry contains a resource's path, its new
+    /// contents, and its previous contents.
+    ///
+    /// Either every resource is written, or none are.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any resource could not be written.
+    fn write_all(&self, files: &[(&Path, &[u8], &[u8])]) -> std::io::Result<()>;
+
+    /// Appends the given bytes onto the end of the resource at the given path, creating it if it
+    /// does not exist.
+    ///
+    /// Unlike [`write`](<Backend::write>), appending is not atomic, and an interrupted append may
+    /// leave only part of the given bytes within the resource.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the resource could not be read or written.
+    fn append(&self, path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+        let mut contents = match self.read(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => vec![],
+            Err(error) => return Err(error),
+        };
+
+        contents.extend_from_slice(bytes);
+
+        self.write(path, &contents)
+    }
+
+    /// Removes the resource at the given path.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the resource does not exist or could not be removed.
+    fn remove(&self, path: &Path) -> std::io::Result<()>;
+
+    /// Returns the entries within the given directory.
+    ///
+    /// Missing directories are treated as empty.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the directory could not be read.
+    fn entries(&self, dir: &Path) -> std::io::Result<Vec<Entry>>;
+
+    /// Acquires an exclusive [`Lock`] over the given path, blocking until it is available.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lock could not be acquired.
+    fn lock(&self, path: &Path) -> std::io::Result<Lock>;
+
+    /// Attempts to acquire an exclusive [`Lock`] over the given path without blocking.
+    ///
+    /// Returns [`None`] if the lock is currently held.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lock could not be acquired.
+    fn try_lock(&self, path: &Path) -> std::io::Result<Option<Lock>>;
+
+    /// Removes every unused lock file whose resource no longer exists, returning the number of
+    /// removed lock files.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the backend could not be traversed, or if a lock file
+    /// could not be locked or removed.
+    fn sweep_locks(&self) -> std::io::Result<usize> {
+        Ok(0)
+    }
+
+    /// Returns the path of the lock file that guards this backend's storage barrier across
+    /// processes, if the backend may be shared between them.
+    ///
+    /// Resources are only changed while holding a shared lock over the file, and snapshots are
+    /// only captured while holding an exclusive lock over it.
+    fn barrier_file(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Returns the unique identifier that was assigned to this backend when it was installed, or
+    /// [`None`] if it was not installed.
+    ///
+    /// Caches and watchers identify resources by this identifier, so they only observe the
+    /// resources of installed backends.
+    fn id(&self) -> Option<u64> {
+        None
+    }
+
+    /// Returns the storage quota of this backend, or [`None`] if it uses the globally installed
+    /// [`Quota`].
+    fn quota(&self) -> Option<Quota> {
+        None
+    }
+
+    /// Cleans up after any interrupted writes, returning the paths of all removed files.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the backend could not be recovered.
+    fn recover(&self) -> std::io::Result<Box<[Box<Path>]>> {
+        Ok(Box::new([]))
+    }
+}
+
+/// Returns the set of filesystem paths that are currently locked within this process.
+///
+/// This is shared between all [`FileSystem`] backends, as they may share a directory.
+fn file_system_locks() -> &'static Arc<LockSet> {
+    static LOCKS: OnceLock<Arc<LockSet>> = OnceLock::new();
+
+    LOCKS.get_or_init(Arc::default)
+}
+
+/// A [`Backend`] that stores resources as files within a directory.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct FileSystem {
+    /// The storage directory.
+    root: Box<Path>,
+}
+
+impl FileSystem {
+    /// Creates a new [`FileSystem`] backend within the given directory.
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self { root: root.as_ref().into() }
+    }
+
+    /// Returns a reference to the storage directory of this [`FileSystem`] backend.
+    #[must_use]
+    pub const fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl Backend for FileSystem {
+    fn exists(&self, path: &Path) -> std::io::Result<bool> {
+        self.root.join(path).try_exists()
+    }
+
+    fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        std::fs::read(self.root.join(path))
+    }
+
+    fn write(&self, path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+        crate::atomic::write(&self.root.join(path), bytes)
+    }
+
+    fn write_all(&self, files: &[(&Path, &[u8], &[u8])]) -> std::io::Result<()> {
+        let paths = files.iter().map(|(p, ..)| self.root.join(p)).collect::<Box<[_]>>();
+        let files = files.iter().zip(paths.iter());
+        let files = files.map(|((_, b, p), path)| (&(**path), *b, *p)).collect::<Box<[_]>>();
+
+        crate::atomic::write_all(&files)
+    }
+
+    fn append(&self, path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+        let path = self.root.join(path);
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        file.write_all(bytes)?;
+        file.sync_data()
+    }
+
+    fn remove(&self, path: &Path) -> std::io::Result<()> {
+        let path = self.root.join(path);
+
+        // The resource's lock file is left in place, as it may still be held or waited on.
+        std::fs::remove_file(path)
+    }
+
+    fn entries(&self, dir: &Path) -> std::io::Result<Vec<Entry>> {
+        let entries = match std::fs::read_dir(self.root.join(dir)) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(error) => return Err(error),
+        };
+
+        entries
+            .filter_map(|entry| {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(error) => return Some(Err(error)),
+                };
+                let path = dir.join(entry.file_name()).into_boxed_path();
+
+                // Lock files and temporary files are implementation details of this backend.
+                if crate::lock::is_lock_file(&path) || crate::atomic::is_temporary(&path) {
+                    return None;
+                }
+
+                Some(entry.file_type().map(|t| Entry { path, is_dir: t.is_dir() }))
+            })
+            .collect()
+    }
+
+    fn lock(&self, path: &Path) -> std::io::Result<Lock> {
+        let path = self.root.join(path);
+
+        Lock::acquire(self::file_system_locks(), &path, Some(&crate::lock::lock_path(&path)))
+    }
+
+    fn try_lock(&self, path: &Path) -> std::io::Result<Option<Lock>> {
+        let path = self.root.join(path);
+
+        Lock::try_acquire(self::file_system_locks(), &path, Some(&crate::lock::lock_path(&path)))
+    }
+
+    fn sweep_locks(&self) -> std::io::Result<usize> {
+        let barrier = self.root.join(crate::lock::BARRIER_FILE);
+        let mut removed = 0;
+        let mut queue = vec![self.root.to_path_buf()];
+
+        while let Some(dir) = queue.pop() {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(error) if error.kind() == ErrorKind::NotFound => continue,
+                Err(error) => return Err(error),
+            };
+
+            for entry in entries {
+                let entry = entry?;
+                let path = entry.path();
+
+                if entry.file_type()?.is_dir() {
+                    queue.push(path);
+
+                    continue;
+                }
+
+                let Some(resource) = crate::lock::resource_path(&path) else {
+                    continue;
+                };
+
+                if path == barrier || resource.try_exists()? {
+                    continue;
+                }
+
+                // Lock files that are currently held are left for a later sweep, and the resource
+                // is checked again as it may have been written before the lock was acquired.
+                let locks = self::file_system_locks();
+                let Some(lock) = Lock::try_acquire(locks, &resource, Some(&path))? else {
+                    continue;
+                };
+
+                if !resource.try_exists()? {
+                    lock.remove_file()?;
+                    removed += 1;
+                }
+            }
+        }
+
+        Ok(removed)
+    }
+
+    fn barrier_file(&self) -> Option<PathBuf> {
+        Some(self.root.join(crate::lock::BARRIER_FILE))
+    }
+
+    fn recover(&self) -> std::io::Result<Box<[Box<Path>]>> {
+        crate::atomic::recover(&self.root)
+    }
+}
+
+/// A [`Backend`] that stores resources in memory.
+///
+/// Resources are lost when the backend is dropped, making this primarily useful for testing.
+#[derive(Debug, Default)]
+pub struct Memory {
+    /// The stored resources.
+    files: Mutex<BTreeMap<PathBuf, Box<[u8]>>>,
+    /// The set of currently locked paths.
+    locks: Arc<LockSet>,
+}
+
+impl Memory {
+    /// Creates a new, empty [`Memory`] backend.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Acquires the stored resources, recovering them if a previous holder panicked.
+    fn files(&self) -> MutexGuard<'_, BTreeMap<PathBuf, Box<[u8]>>> {
+        // Every operation leaves the map in a consistent state, so poisoning can be safely ignored.
+        self.files.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Backend for Memory {
+    fn exists(&self, path: &Path) -> std::io::Result<bool> {
+        Ok(self.files().contains_key(path))
+    }
+
+    fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        self.files().get(path).map(|b| b.to_vec()).ok_or_else(|| ErrorKind::NotFound.into())
+    }
+
+    fn write(&self, path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+        self.files().insert(path.to_path_buf(), bytes.into());
+
+        Ok(())
+    }
+
+    fn write_all(&self, files: &[(&Path, &[u8], &[u8])]) -> std::io::Result<()> {
+        let files = files.iter().map(|(p, b, _)| (p.to_path_buf(), Box::from(*b)));
+
+        self.files().extend(files);
+
+        Ok(())
+    }
+
+    fn append(&self, path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+        let mut files = self.files();
+        let contents = files.entry(path.to_path_buf()).or_default();
+
+        *contents = [&(**contents), bytes].concat().into_boxed_slice();
+
+        drop(files);
+
+        Ok(())
+    }
+
+    fn remove(&self, path: &Path) -> std::io::Result<()> {
+        self.files().remove(path).map(drop).ok_or_else(|| ErrorKind::NotFound.into())
+    }
+
+    fn entries(&self, dir: &Path) -> std::io::Result<Vec<Entry>> {
+        let mut entries = BTreeMap::new();
+
+        for path in self.files().keys() {
+            let Ok(relative) = path.strip_prefix(dir) else {
+                continue;
+            };
+            let mut components = relative.components();
+            let Some(name) = components.next() else {
+                continue;
+            };
+            let is_dir = components.next().is_some();
+
+            entries.entry(dir.join(name)).or_insert(is_dir);
+        }
+
+        Ok(entries.into_iter().map(|(p, is_dir)| Entry { path: p.into(), is_dir }).collect())
+    }
+
+    fn lock(&self, path: &Path) -> std::io::Result<Lock> {
+        Lock::acquire(&self.locks, path, None)
+    }
+
+    fn try_lock(&self, path: &Path) -> std::io::Result<Option<Lock>> {
+        Lock::try_acquire(&self.locks, path, None)
+    }
+}
+
+/// A [`Backend`] that has been installed, and has been assigned a unique identifier.
+#[derive(Debug)]
+pub struct Installed<B> {
+    /// The assigned identifier.
+    id: u64,
+    /// The installed backend.
+    backend: B,
+    /// The backend's storage quota, if it overrides the globally installed quota.
+    quota: Option<Quota>,
+}
+
+impl<B: Backend> Installed<B> {
+    /// Assigns a new unique identifier to the given backend.
+    pub fn new(backend: B) -> Self {
+        /// The identifier assigned to the next installed backend.
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+        Self { id: NEXT_ID.fetch_add(1, Ordering::Relaxed), backend, quota: None }
+    }
+
+    /// Returns this [`Installed<B>`] with the given storage quota, which overrides the globally
+    /// installed quota.
+    #[must_use]
+    pub const fn with_quota(mut self, quota: Quota) -> Self {
+        self.quota = Some(quota);
+
+        self
+    }
+}
+
+impl<B: Backend> Backend for Installed<B> {
+    fn exists(&self, path: &Path) -> std::io::Result<bool> {
+        self.backend.exists(path)
+    }
+
+    fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        self.backend.read(path)
+    }
+
+    fn write(&self, path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+        self.backend.write(path, bytes)
+    }
+
+    fn write_all(&self, files: &[(&Path, &[u8], &[u8])]) -> std::io::Result<()> {
+        self.backend.write_all(files)
+    }
+
+    fn append(&self, path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+        self.backend.append(path, bytes)
+    }
+
+    fn remove(&self, path: &Path) -> std::io::Result<()> {
+        self.backend.remove(path)
+    }
+
+    fn entries(&self, dir: &Path) -> std::io::Result<Vec<Entry>> {
+        self.backend.entries(dir)
+    }
+
+    fn lock(&self, path: &Path) -> std::io::Result<Lock> {
+        self.backend.lock(path)
+    }
+
+    fn try_lock(&self, path: &Path) -> std::io::Result<Option<Lock>> {
+        self.backend.try_lock(path)
+    }
+
+    fn sweep_locks(&self) -> std::io::Result<usize> {
+        self.backend.sweep_locks()
+    }
+
+    fn barrier_file(&self) -> Option<PathBuf> {
+        self.backend.barrier_file()
+    }
+
+    fn id(&self) -> Option<u64> {
+        Some(self.id)
+    }
+
+    fn quota(&self) -> Option<Quota> {
+        self.quota.or_else(|| self.backend.quota())
+    }
+
+    fn recover(&self) -> std::io::Result<Box<[Box<Path>]>> {
+        self.backend.recover()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{Backend, FileSystem};
+
+    #[cfg(unix)]
+    #[test]
+    fn sweep_unused_lock_files() -> std::io::Result<()> {
+        let root = std::env::temp_dir().join(format!("doop-storage-locks-{}", std::process::id()));
+        let backend = FileSystem::new(&root);
+        let (a, b) = (Path::new("a/b.json"), Path::new("a/c.json"));
+
+        backend.write(a, b"{}")?;
+        backend.write(b, b"{}")?;
+        drop(backend.lock(a)?);
+
+        let held = backend.lock(b)?;
+
+        backend.remove(a)?;
+        backend.remove(b)?;
+
+        assert!(root.join("a/.b.json.lock").try_exists()?);
+        assert_eq!(backend.sweep_locks()?, 1);
+        assert!(!root.join("a/.b.json.lock").try_exists()?);
+
+        drop(held);
+
+        assert_eq!(backend.sweep_locks()?, 1);
+        assert!(!root.join("a/.c.json.lock").try_exists()?);
+
+        std::fs::remove_dir_all(root)
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Debug, Display, Formatter};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::Backend;
+
+/// Identifies a single cached value by its backend's identifier and its path.
+type CacheKey = (u64, Box<Path>);
+
+/// Returns the cache key of the given path within the given backend, or [`None`] if the backend
+/// was not installed.
+fn cache_key(backend: &Arc<dyn Backend>, path: &Path) -> Option<CacheKey> {
+    backend.id().map(|id| (id, path.into()))
+}
+
+/// The entries of a [`Cache<T>`], ordered by their most recent use.
+#[derive(Debug)]
+struct Entries<T> {
+    /// The cached values alongside the tick of their most recent use.
+    values: HashMap<CacheKey, (T, u64)>,
+    /// The keys of the cached values, ordered by the tick of their most recent use.
+    order: BTreeMap<u64, CacheKey>,
+    /// The tick assigned to the next use.
+    tick: u64,
+    /// Incremented whenever a value is inserted or removed.
+    generation: u64,
+    /// The keys of the values that were written within this process.
+    written: HashSet<CacheKey>,
+}
+
+impl<T> Entries<T> {
+    /// Marks the given key as the most recently used key, returning its value.
+    fn touch(&mut self, key: &CacheKey) -> Option<&T> {
+        let (value, tick) = self.values.get_mut(key)?;
+        let key = self.order.remove(tick)?;
+
+        *tick = self.tick;
+        self.order.insert(self.tick, key);
+        self.tick += 1;
+
+        Some(value)
+    }
+
+    /// Inserts the given value, evicting the least recently used values if necessary.
+    fn insert(&mut self, key: CacheKey, value: T, capacity: usize) {
+        self.remove(&key);
+
+        while self.values.len() >= capacity {
+            let Some((_, evicted)) = self.order.pop_first() else {
+                break;
+            };
+
+            self.values.remove(&evicted);
+        }
+
+        if capacity > 0 {
+            self.order.insert(self.tick, key.clone());
+            self.values.insert(key, (value, self.tick));
+            self.tick += 1;
+        }
+
+        self.generation += 1;
+    }
+
+    /// Removes the value of the given key.
+    fn remove(&mut self, key: &CacheKey) {
+        if let Some((_, tick)) = self.values.remove(key) {
+            self.order.remove(&tick);
+        }
+
+        self.generation += 1;
+    }
+}
+
+/// A snapshot of the statistics of a [`Cache<T>`].
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The number of reads that were served from the cache.
+    pub hits: u64,
+    /// The number of reads that were not served from the cache.
+    pub misses: u64,
+    /// The number of cached values.
+    pub len: usize,
+    /// The maximum number of cached values.
+    pub capacity: usize,
+}
+
+impl Display for CacheStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Self { hits, misses, len, capacity } = self;
+
+        write!(f, "{hits} hits, {misses} misses, {len}/{capacity} entries")
+    }
+}
+
+/// A bounded, least-recently-used cache of decoded values.
+///
+/// A cache is attached to keys through [`Key::with_cache`](<crate::Key::with_cache>), and may be
+/// shared between any number of keys. Reads are served from the cache when possible, and values
+/// are invalidated whenever they are written or removed through a key.
+///
+/// Only changes made through keys within this process, or reported by a
+/// [`Watcher<T>`](<crate::Watcher>), are observed, so a cache is only used for resources that are
+/// being watched or that have been written within this process since the cache was last cleared.
+/// If a backend is modified in any other way, such as by [sweeping](<crate::sweep_expired>)
+/// expired values or restoring a snapshot, the cache should be [cleared](<Cache::clear>). Only
+/// the resources of installed backends are cached.
+pub struct Cache<T> {
+    /// The maximum number of cached values.
+    capacity: usize,
+    /// The cached values.
+    entries: Mutex<Entries<T>>,
+    /// The number of reads that were served from the cache.
+    hits: AtomicU64,
+    /// The number of reads that were not served from the cache.
+    misses: AtomicU64,
+}
+
+impl<T> Cache<T> {
+    /// Creates a new, empty [`Cache<T>`] that holds at most the given number of values.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        let entries = Entries {
+            values: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            generation: 0,
+            written: HashSet::new(),
+        };
+
+        Self {
+            capacity,
+            entries: Mutex::new(entries),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Acquires the cached values, recovering them if a previous holder panicked.
+    fn entries(&self) -> MutexGuard<'_, Entries<T>> {
+        // Every operation leaves the entries in a consistent state, so poisoning can be safely
+        // ignored.
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the maximum number of values within this [`Cache<T>`].
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of values within this [`Cache<T>`].
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries().values.len()
+    }
+
+    /// Returns whether this [`Cache<T>`] is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries().values.is_empty()
+    }
+
+    /// Returns the current statistics of this [`Cache<T>`].
+    #[must_use]
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            len: self.len(),
+            capacity: self.capacity,
+        }
+    }
+
+    /// Removes the cached value at the given path, invalidating any pending insertions.
+    pub fn invalidate(&self, backend: &Arc<dyn Backend>, path: &Path) {
+        if let Some(key) = self::cache_key(backend, path) {
+            self.entries().remove(&key);
+        }
+    }
+
+    /// Removes every value from this [`Cache<T>`].
+    pub fn clear(&self) {
+        let mut entries = self.entries();
+
+        entries.values.clear();
+        entries.order.clear();
+        entries.written.clear();
+        entries.generation += 1;
+    }
+}
+
+impl<T> Debug for Cache<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Values are intentionally omitted, as they may not be printable.
+        f.debug_struct("Cache").field("stats", &self.stats()).finish()
+    }
+}
+
+/// A [`Cache<T>`] that has been attached to a key.
+///
+/// This allows keys to use caches without requiring their values to be cloneable everywhere.
+pub trait Cached<T>: Debug + Send + Sync {
+    /// Returns the cached value at the given path.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the cache's current generation if there is no cached value.
+    fn get(&self, backend: &Arc<dyn Backend>, path: &Path) -> Result<T, u64>;
+
+    /// Caches the given value at the given path if the cache has not been modified since the
+    /// given generation.
+    fn insert(&self, backend: &Arc<dyn Backend>, path: &Path, value: &T, generation: u64);
+
+    /// Removes the cached value at the given path, invalidating any pending insertions.
+    fn remove(&self, backend: &Arc<dyn Backend>, path: &Path);
+
+    /// Removes the cached value at the given path after it was written within this process,
+    /// allowing it to be cached even while it is not being watched.
+    fn written(&self, backend: &Arc<dyn Backend>, path: &Path);
+
+    /// Returns whether the value at the given path was written within this process since the
+    /// cache was last cleared.
+    fn is_written(&self, backend: &Arc<dyn Backend>, path: &Path) -> bool;
+}
+
+impl<T: Clone + Send> Cached<T> for Cache<T> {
+    fn get(&self, backend: &Arc<dyn Backend>, path: &Path) -> Result<T, u64> {
+        let key = self::cache_key(backend, path);
+        let mut entries = self.entries();
+
+        if let Some(value) = key.and_then(|k| entries.touch(&k)) {
+            let value = value.clone();
+
+            drop(entries);
+            self.hits.fetch_add(1, Ordering::Relaxed);
+
+            return Ok(value);
+        }
+
+        let generation = entries.generation;
+
+        drop(entries);
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        Err(generation)
+    }
+
+    fn insert(&self, backend: &Arc<dyn Backend>, path: &Path, value: &T, generation: u64) {
+        let Some(key) = self::cache_key(backend, path) else {
+            return;
+        };
+        let mut entries = self.entries();
+
+        // Another thread may have written a newer value while this value was being read.
+        if generation != entries.generation {
+            return;
+        }
+
+        entries.insert(key, value.clone(), self.capacity);
+    }
+
+    fn remove(&self, backend: &Arc<dyn Backend>, path: &Path) {
+        self.invalidate(backend, path);
+    }
+
+    fn written(&self, backend: &Arc<dyn Backend>, path: &Path) {
+        let Some(key) = self::cache_key(backend, path) else {
+            return;
+        };
+        let mut entries = self.entries();
+
+        entries.remove(&key);
+        entries.written.insert(key);
+    }
+
+    fn is_written(&self, backend: &Arc<dyn Backend>, path: &Path) -> bool {
+        self::cache_key(backend, path).is_some_and(|k| self.entries().written.contains(&k))
+    }
+}
@@ -1,4 +1,6 @@
+use std::fmt::Debug;
 use std::io::Write;
+use std::marker::PhantomData;
 
 use flate2::write::{GzDecoder, GzEncoder};
 use flate2::Compression;
@@ -6,6 +8,185 @@ use serde::{Deserialize, Serialize};
 
 use crate::{Error, Format};
 
+/// A compression algorithm used by [`Compress<F, C, A>`].
+pub trait Algorithm: Debug {
+    /// The bytes that every payload compressed by this [`Algorithm`] begins with.
+    ///
+    /// These are used to detect the algorithm that was used to compress a payload.
+    const MAGIC: &'static [u8];
+
+    /// Compresses the given bytes using the given compression level between `0`-`9`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the bytes could not be compressed.
+    fn compress(bytes: &[u8], level: u8) -> std::io::Result<Vec<u8>>;
+
+    /// Decompresses the given bytes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the bytes could not be decompressed.
+    fn decompress(bytes: &[u8]) -> std::io::Result<Vec<u8>>;
+}
+
+/// The [gzip](https://www.gzip.org/) compression algorithm.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Gzip;
+
+impl Algorithm for Gzip {
+    const MAGIC: &'static [u8] = &[0x1F, 0x8B];
+
+    fn compress(bytes: &[u8], level: u8) -> std::io::Result<Vec<u8>> {
+        let buffer = Vec::with_capacity(bytes.len());
+        let mut encoder = GzEncoder::new(buffer, Compression::new(level.into()));
+
+        encoder.write_all(bytes)?;
+        encoder.finish()
+    }
+
+    fn decompress(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+        let buffer = Vec::with_capacity(bytes.len());
+        let mut decoder = GzDecoder::new(buffer);
+
+        decoder.write_all(bytes)?;
+        decoder.finish()
+    }
+}
+
+/// Stores the installed Zstandard dictionary.
+#[cfg(feature = "zstd")]
+static DICTIONARY: std::sync::RwLock<Option<std::sync::Arc<[u8]>>> = std::sync::RwLock::new(None);
+
+/// Installs the given dictionary for all [`Zstd`] compression, replacing any previous dictionary.
+///
+/// Payloads that were compressed using a dictionary can only be decompressed while that same
+/// dictionary is installed.
+#[cfg(feature = "zstd")]
+pub fn install_zstd_dictionary(dictionary: impl Into<std::sync::Arc<[u8]>>) {
+    let mut installed = DICTIONARY.write().unwrap_or_else(std::sync::PoisonError::into_inner);
+
+    *installed = Some(dictionary.into());
+}
+
+/// Trains a new [`Zstd`] dictionary from the given samples, with the given maximum size in bytes.
+///
+/// # Errors
+///
+/// This function will return an error if the dictionary could not be trained.
+#[cfg(feature = "zstd")]
+pub fn train_zstd_dictionary(
+    samples: &[impl AsRef<[u8]>],
+    max_size: usize,
+) -> std::io::Result<Vec<u8>> {
+    zstd::dict::from_samples(samples, max_size)
+}
+
+/// The [Zstandard](https://facebook.github.io/zstd/) compression algorithm.
+///
+/// If a dictionary has been installed through [`install_zstd_dictionary`], it is used for all
+/// compression.
+#[cfg(feature = "zstd")]
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Zstd;
+
+#[cfg(feature = "zstd")]
+impl Zstd {
+    /// Returns the installed dictionary.
+    fn dictionary() -> Option<std::sync::Arc<[u8]>> {
+        DICTIONARY.read().unwrap_or_else(std::sync::PoisonError::into_inner).clone()
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl Algorithm for Zstd {
+    const MAGIC: &'static [u8] = &[0x28, 0xB5, 0x2F, 0xFD];
+
+    fn compress(bytes: &[u8], level: u8) -> std::io::Result<Vec<u8>> {
+        let Some(dictionary) = Self::dictionary() else {
+            return zstd::encode_all(bytes, level.into());
+        };
+
+        zstd::bulk::Compressor::with_dictionary(level.into(), &dictionary)?.compress(bytes)
+    }
+
+    fn decompress(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+        use std::io::Read;
+
+        if zstd::zstd_safe::get_dict_id_from_frame(bytes).is_none() {
+            return zstd::decode_all(bytes);
+        }
+
+        let Some(dictionary) = Self::dictionary() else {
+            return Err(std::io::Error::other("missing zstd dictionary"));
+        };
+        let mut buffer = Vec::with_capacity(bytes.len());
+
+        zstd::Decoder::with_dictionary(bytes, &dictionary)?.read_to_end(&mut buffer)?;
+
+        Ok(buffer)
+    }
+}
+
+/// The [Brotli](https://github.com/google/brotli) compression algorithm.
+///
+/// Brotli streams do not have a header, so compressed payloads are prefixed with [`MAGIC`] to
+/// allow them to be detected.
+///
+/// [`MAGIC`]: <Algorithm::MAGIC>
+#[cfg(feature = "brotli")]
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Brotli;
+
+#[cfg(feature = "brotli")]
+impl Algorithm for Brotli {
+    const MAGIC: &'static [u8] = b"\xCE\xB2\xCF\x81";
+
+    fn compress(bytes: &[u8], level: u8) -> std::io::Result<Vec<u8>> {
+        let buffer = Self::MAGIC.to_vec();
+        let mut encoder = brotli::CompressorWriter::new(buffer, 4096, level.into(), 22);
+
+        encoder.write_all(bytes)?;
+
+        Ok(encoder.into_inner())
+    }
+
+    fn decompress(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+        let bytes = bytes.strip_prefix(Self::MAGIC).unwrap_or(bytes);
+        let buffer = Vec::with_capacity(bytes.len());
+        let mut decoder = brotli::DecompressorWriter::new(buffer, 4096);
+
+        decoder.write_all(bytes)?;
+        decoder.into_inner().map_err(|_| std::io::ErrorKind::UnexpectedEof.into())
+    }
+}
+
+/// Decompresses the given bytes, detecting the algorithm that was used to compress them.
+///
+/// If no supported algorithm is detected, `A` is assumed.
+///
+/// # Errors
+///
+/// This function will return an error if the bytes could not be decompressed.
+fn decompress<A: Algorithm>(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    if bytes.starts_with(A::MAGIC) {
+        return A::decompress(bytes);
+    }
+    if bytes.starts_with(Gzip::MAGIC) {
+        return Gzip::decompress(bytes);
+    }
+    #[cfg(feature = "zstd")]
+    if bytes.starts_with(Zstd::MAGIC) {
+        return Zstd::decompress(bytes);
+    }
+    #[cfg(feature = "brotli")]
+    if bytes.starts_with(Brotli::MAGIC) {
+        return Brotli::decompress(bytes);
+    }
+
+    A::decompress(bytes)
+}
+
 /// A [`Format`] that automatically compresses and decompresses data.
 ///
 /// Using this wrapper ensures that all values will, assuming that `C` is non-zero:
@@ -15,51 +196,86 @@ use crate::{Error, Format};
 /// The generic constant `C` should be assigned a value between `0`-`9`, with `0` representing no
 /// compression and `9` representing the slowest (but best) compression level. By default, `C` is
 /// assigned to `5` for a middle-of-the-road, average compression level.
+///
+/// Values are compressed using the [`Algorithm`] `A`, which is [`Gzip`] by default. Values are
+/// always decompressed using the algorithm that they were compressed with, so the algorithm can be
+/// changed without affecting previously stored values.
 #[repr(transparent)]
 #[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Compress<F: Format, const C: u8 = 5>(F);
+pub struct Compress<F: Format, const C: u8 = 5, A: Algorithm = Gzip>(F, PhantomData<A>);
 
-impl<F: Format, const C: u8> Compress<F, C> {
-    /// Creates a new [`Compress<F, C>`].
+impl<F: Format, const C: u8, A: Algorithm> Compress<F, C, A> {
+    /// Creates a new [`Compress<F, C, A>`].
     pub const fn new(format: F) -> Self {
-        Self(format)
+        Self(format, PhantomData)
     }
 
-    /// Returns a reference to the inner [`Format`] of this [`Compress<F, C>`].
+    /// Returns a reference to the inner [`Format`] of this [`Compress<F, C, A>`].
     pub const fn inner(&self) -> &F {
         &self.0
     }
 
-    /// Returns the [`Compression`] level of this [`Compress<F, C>`].
-    pub const fn compression(&self) -> Compression {
+    /// Returns the compression level of this [`Compress<F, C, A>`].
+    pub const fn level(&self) -> u8 {
         debug_assert!(C <= 9);
 
-        Compression::new(C as u32)
+        C
     }
 }
 
-impl<F: Format, const C: u8> Format for Compress<F, C> {
+impl<F: Format, const C: u8, A: Algorithm> Format for Compress<F, C, A> {
     type EncodingError = Error<F>;
     type DecodingError = Error<F>;
 
+    const SELF_DESCRIBING: bool = F::SELF_DESCRIBING;
+
     fn extension(&self) -> String {
         self.0.extension() + "x"
     }
 
     fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, Self::EncodingError> {
         let bytes = self.inner().encode(value).map_err(Error::Encoding)?;
-        let buffer = Vec::with_capacity(bytes.len());
-        let mut encoder = GzEncoder::new(buffer, self.compression());
 
-        encoder.write_all(&bytes)?;
-        encoder.finish().map_err(Into::into)
+        A::compress(&bytes, self.level()).map_err(Into::into)
     }
 
     fn decode<T: for<'de> Deserialize<'de>>(&self, bytes: &[u8]) -> Result<T, Self::DecodingError> {
-        let buffer = Vec::with_capacity(bytes.len());
-        let mut decoder = GzDecoder::new(buffer);
+        self.inner().decode(&self::decompress::<A>(bytes)?).map_err(Error::Decoding)
+    }
+}
 
-        decoder.write_all(bytes)?;
-        self.inner().decode(&decoder.finish()?).map_err(Error::Decoding)
+#[cfg(test)]
+mod tests {
+    use super::{Algorithm, Compress, Gzip};
+    use crate::tests::Plain;
+    use crate::{Error, Format};
+
+    #[test]
+    fn detects_compression_algorithm() -> Result<(), Error<Plain>> {
+        let gzip = Compress::<Plain, 5, Gzip>::new(Plain);
+        let bytes = gzip.encode(&"value")?;
+
+        assert!(bytes.starts_with(Gzip::MAGIC));
+        assert_eq!(gzip.decode::<String>(&bytes)?, "value");
+
+        // Values are decompressed using the algorithm that they were compressed with.
+        #[cfg(feature = "zstd")]
+        {
+            let zstd = Compress::<Plain, 5, super::Zstd>::new(Plain);
+
+            assert_eq!(zstd.decode::<String>(&bytes)?, "value");
+            assert_eq!(gzip.decode::<String>(&zstd.encode(&"value")?)?, "value");
+        }
+        #[cfg(feature = "brotli")]
+        {
+            let brotli = Compress::<Plain, 5, super::Brotli>::new(Plain);
+
+            assert_eq!(brotli.decode::<String>(&bytes)?, "value");
+            assert_eq!(gzip.decode::<String>(&brotli.encode(&"value")?)?, "value");
+        }
+
+        assert!(gzip.decode::<String>(b"\"value\"").is_err());
+
+        Ok(())
     }
 }
@@ -1,14 +1,65 @@
-use std::io::{Read, Write};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::path::Path;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
 
 use argon2::password_hash::rand_core::RngCore;
 use argon2::Argon2;
 use chacha20poly1305::aead::stream::{DecryptorBE32, EncryptorBE32};
-use chacha20poly1305::aead::OsRng;
+use chacha20poly1305::aead::{OsRng, Payload};
 use chacha20poly1305::{KeyInit, XChaCha20Poly1305};
+use hkdf::Hkdf;
 use serde::{Deserialize, Serialize};
-use zeroize::Zeroize;
+use sha2::Sha256;
+use zeroize::Zeroizing;
 
-use crate::{Error, Format};
+use crate::{Error, Format, Stored};
+
+/// The bytes that precede every encrypted payload that contains a header.
+const MAGIC: [u8; 4] = *b"DPZ\0";
+/// The current encrypted payload version.
+///
+/// Version `1` payloads derive each file's key directly from the keyring's key using Argon2,
+/// while version `2` payloads derive a subkey from a cached master key using HKDF.
+const VERSION: u8 = 2;
+/// The salt prefix used while deriving a master key.
+const MASTER_SALT: &[u8] = b"doop-storage/master/";
+/// The context used while deriving a subkey from a master key.
+const SUBKEY_INFO: &[u8] = b"doop-storage/subkey/v2";
+/// The length of an encrypted payload's header.
+const HEADER_LEN: usize = MAGIC.len() + 1 + std::mem::size_of::<KeyId>();
+/// The length of an encrypted payload's salt.
+const SALT_LEN: usize = 32;
+/// The length of an encrypted payload's nonce.
+const NONCE_LEN: usize = 19;
+/// The length of an encrypted payload's authentication tag.
+const TAG_LEN: usize = 16;
+
+/// Stores the installed keyring.
+static KEYRING: RwLock<Option<Arc<Keyring>>> = RwLock::new(None);
+
+/// Identifies a single key within a [`Keyring`].
+pub type KeyId = u32;
+
+/// An error that may occur while loading a [`Keyring`].
+#[derive(Debug, thiserror::Error)]
+pub enum KeyringError {
+    /// An error during environment variable fetching.
+    #[error(transparent)]
+    Var(#[from] std::env::VarError),
+    /// An error while reading a key file.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// A key file contained an invalid line.
+    #[error("invalid key on line {0}")]
+    Invalid(usize),
+    /// A key file contained the same key identifier more than once.
+    #[error("duplicate key identifier {0}")]
+    Duplicate(KeyId),
+    /// A key file did not contain any keys.
+    #[error("missing encryption keys")]
+    Empty,
+}
 
 /// An error that may occur during encryption or decryption.
 #[derive(Debug, thiserror::Error)]
@@ -16,12 +67,18 @@ pub enum EncryptError<F: Format> {
     /// A format error.
     #[error(transparent)]
     Crate(#[from] crate::Error<F>),
-    /// An error during environment variable fetching.
+    /// An error while loading the keyring.
     #[error(transparent)]
-    Var(#[from] std::env::VarError),
+    Keyring(#[from] KeyringError),
+    /// A payload was encrypted with a key that is missing from the keyring.
+    #[error("missing encryption key {0}")]
+    MissingKey(KeyId),
     /// An error during password hashing.
     #[error("{0}")]
     Argon(argon2::Error),
+    /// An error during subkey derivation.
+    #[error("{0}")]
+    Expand(hkdf::InvalidLength),
     /// An error during random number generation.
     #[error("{0}")]
     Random(argon2::password_hash::rand_core::Error),
@@ -30,7 +87,239 @@ pub enum EncryptError<F: Format> {
     Encrypt(chacha20poly1305::Error),
 }
 
+/// A single key within a [`Keyring`].
+#[derive(Clone)]
+struct Secret {
+    /// The key.
+    key: Zeroizing<Box<[u8]>>,
+    /// The master key derived from the key, once it has been derived.
+    master: OnceLock<Zeroizing<[u8; 32]>>,
+}
+
+impl Secret {
+    /// Creates a new [`Secret`].
+    fn new(key: impl Into<Box<[u8]>>) -> Self {
+        Self { key: Zeroizing::new(key.into()), master: OnceLock::new() }
+    }
+}
+
+/// A set of encryption keys, containing one active key and any number of decrypt-only keys.
+///
+/// Values are always encrypted using the active key, and may be decrypted using any key.
+#[derive(Clone)]
+pub struct Keyring {
+    /// The identifier of the active key.
+    active: KeyId,
+    /// The contained keys.
+    keys: BTreeMap<KeyId, Secret>,
+}
+
+impl Keyring {
+    /// Creates a new [`Keyring`] with the given active key.
+    pub fn new(id: KeyId, key: impl Into<Box<[u8]>>) -> Self {
+        Self { active: id, keys: BTreeMap::from([(id, Secret::new(key))]) }
+    }
+
+    /// Returns this [`Keyring`] with the given decrypt-only key.
+    ///
+    /// If the identifier matches the active key, the active key is replaced.
+    #[must_use]
+    pub fn with_key(mut self, id: KeyId, key: impl Into<Box<[u8]>>) -> Self {
+        self.keys.insert(id, Secret::new(key));
+
+        self
+    }
+
+    /// Returns this [`Keyring`] with the given active key, keeping the previously active key as a
+    /// decrypt-only key.
+    #[must_use]
+    pub fn with_active(mut self, id: KeyId, key: impl Into<Box<[u8]>>) -> Self {
+        self.active = id;
+
+        self.with_key(id, key)
+    }
+
+    /// Returns the identifier of the active key of this [`Keyring`].
+    #[must_use]
+    pub const fn active(&self) -> KeyId {
+        self.active
+    }
+
+    /// Returns the identifiers of every key within this [`Keyring`].
+    pub fn ids(&self) -> impl Iterator<Item = KeyId> + '_ {
+        self.keys.keys().copied()
+    }
+
+    /// Returns the key with the given identifier.
+    fn get(&self, id: KeyId) -> Option<&[u8]> {
+        self.keys.get(&id).map(|s| &(**s.key))
+    }
+
+    /// Returns the master key derived from the key with the given identifier, deriving it if it
+    /// has not yet been derived.
+    ///
+    /// Master keys are derived using Argon2, which is intentionally slow, so each key is only
+    /// derived once for each [`Keyring`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the key could not be hashed.
+    fn master(&self, id: KeyId) -> Result<Option<&[u8; 32]>, argon2::Error> {
+        let Some(secret) = self.keys.get(&id) else {
+            return Ok(None);
+        };
+
+        if secret.master.get().is_none() {
+            let salt = [MASTER_SALT, &id.to_be_bytes()].concat();
+            let mut master = Zeroizing::new([0; 32]);
+
+            Argon2::default().hash_password_into(&secret.key, &salt, &mut (*master))?;
+
+            // Another thread may have derived the same key in the meantime, which is harmless.
+            secret.master.set(master).ok();
+        }
+
+        Ok(secret.master.get().map(|m| &(**m)))
+    }
+
+    /// Parses a new [`Keyring`] from the contents of a key file.
+    ///
+    /// Each line contains a key identifier and a key separated by the first `=`, such as
+    /// `2=hunter2`, and the key with the highest identifier is active. A line that does not start
+    /// with an identifier is treated entirely as key `0`. Surrounding whitespace is trimmed from
+    /// identifiers and keys, and blank lines and lines starting with `#` are ignored.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a line is invalid, if an identifier is repeated, or
+    /// if there are no keys.
+    pub fn parse(text: &str) -> Result<Self, KeyringError> {
+        let mut keys = BTreeMap::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // Keys may contain `=` themselves, such as padded base64, so the line is only split if
+            // it starts with a valid identifier.
+            let (id, key) = line
+                .split_once('=')
+                .and_then(|(id, key)| Some((id.trim().parse().ok()?, key.trim())))
+                .unwrap_or((0, line));
+
+            if key.is_empty() {
+                return Err(KeyringError::Invalid(index + 1));
+            }
+            if keys.insert(id, Secret::new(key.as_bytes())).is_some() {
+                return Err(KeyringError::Duplicate(id));
+            }
+        }
+
+        let active = *keys.keys().next_back().ok_or(KeyringError::Empty)?;
+
+        Ok(Self { active, keys })
+    }
+
+    /// Loads a new [`Keyring`] from the key file at the given path.
+    ///
+    /// See [`parse`](<Keyring::parse>) for the expected file format.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file could not be read or parsed.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, KeyringError> {
+        let text = Zeroizing::new(std::fs::read_to_string(path)?);
+
+        Self::parse(&text)
+    }
+
+    /// Loads a new [`Keyring`] from the environment.
+    ///
+    /// If the `ENCRYPTION_KEY_FILE` environment variable is set, the keyring is loaded from that
+    /// key file. Otherwise, the `ENCRYPTION_KEY` environment variable is used as key `0`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if neither variable is set, or if the key file could
+    /// not be read or parsed.
+    pub fn from_env() -> Result<Self, KeyringError> {
+        if let Some(path) = std::env::var_os("ENCRYPTION_KEY_FILE") {
+            return Self::load(path);
+        }
+
+        Ok(Self::new(0, std::env::var("ENCRYPTION_KEY")?.into_bytes()))
+    }
+}
+
+impl Debug for Keyring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Keys are intentionally omitted to avoid leaking them into logs.
+        f.debug_struct("Keyring")
+            .field("active", &self.active)
+            .field("ids", &self.keys.keys())
+            .finish()
+    }
+}
+
+/// Installs the given keyring for all [`Encrypt<F>`] formats, replacing any previous keyring.
+///
+/// If no keyring is installed, one is loaded from the environment on first use.
+pub fn install_keyring(keyring: Keyring) {
+    *KEYRING.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(keyring));
+}
+
+/// Returns the installed keyring, loading it from the environment if necessary.
+///
+/// # Errors
+///
+/// This function will return an error if the keyring could not be loaded.
+fn keyring() -> Result<Arc<Keyring>, KeyringError> {
+    if let Some(keyring) = &(*KEYRING.read().unwrap_or_else(PoisonError::into_inner)) {
+        return Ok(Arc::clone(keyring));
+    }
+
+    let keyring = Arc::new(Keyring::from_env()?);
+    let mut installed = KEYRING.write().unwrap_or_else(PoisonError::into_inner);
+
+    Ok(Arc::clone(installed.get_or_insert(keyring)))
+}
+
+/// Re-encrypts every outdated stored value of the given type, returning the number of values.
+///
+/// A value is outdated if it was not encrypted using the active key of the installed keyring, or
+/// if it uses an older payload version.
+///
+/// # Errors
+///
+/// This function will return an error if the values could not be listed, read, or written.
+pub fn rotate_encrypted<T, F>() -> Result<usize, Error<Encrypt<F>>>
+where
+    T: Stored<Format = Encrypt<F>>,
+    T::Arguments: Clone,
+    F: Format,
+{
+    let active = self::keyring().map_err(|e| Error::Encoding(e.into()))?.active();
+    let mut rotated = 0;
+
+    for (_, key) in T::list()? {
+        if Encrypt::<F>::header(&key.backend().read(key.path())?) == Some((VERSION, active)) {
+            continue;
+        }
+
+        key.update(|_| ())?;
+        rotated += 1;
+    }
+
+    Ok(rotated)
+}
+
 /// A format that automatically encrypts and decrypts data.
+///
+/// Values are encrypted using the active key of the installed [`Keyring`], and the key's
+/// identifier is stored alongside the encrypted value.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Encrypt<F: Format>(F);
 
@@ -45,15 +334,109 @@ impl<F: Format> Encrypt<F> {
         &self.0
     }
 
-    /// Returns the encryption password from the environment.
+    /// Returns the identifier of the key that was used to encrypt the given payload.
     ///
-    /// This can be configured using the `ENCRYPTION_KEY` environment variable.
+    /// Returns [`None`] if the payload does not contain a header, which is the case for payloads
+    /// that were encrypted before keys had identifiers.
+    #[must_use]
+    pub fn key_id(bytes: &[u8]) -> Option<KeyId> {
+        Self::header(bytes).map(|(_, id)| id)
+    }
+
+    /// Returns the payload version and key identifier stored within the given payload's header.
+    fn header(bytes: &[u8]) -> Option<(u8, KeyId)> {
+        let header = bytes.get(.. HEADER_LEN)?;
+        let version = header[MAGIC.len()];
+
+        if header[.. MAGIC.len()] != MAGIC || !(1 ..= VERSION).contains(&version) {
+            return None;
+        }
+
+        header[MAGIC.len() + 1 ..].try_into().ok().map(|b| (version, KeyId::from_be_bytes(b)))
+    }
+
+    /// Creates a cipher from the given master key and salt.
     ///
     /// # Errors
     ///
-    /// This function will return an error if .
-    fn password() -> Result<Box<[u8]>, std::env::VarError> {
-        Ok(std::env::var("ENCRYPTION_KEY")?.into_bytes().into_boxed_slice())
+    /// This function will return an error if the subkey could not be derived.
+    fn cipher(master: &[u8; 32], salt: &[u8]) -> Result<XChaCha20Poly1305, EncryptError<F>> {
+        let mut subkey = Zeroizing::new([0; 32]);
+
+        Hkdf::<Sha256>::new(Some(salt), master)
+            .expand(SUBKEY_INFO, &mut (*subkey))
+            .map_err(EncryptError::Expand)?;
+
+        Ok(XChaCha20Poly1305::new(&(*subkey).into()))
+    }
+
+    /// Creates a cipher from the given key and salt, as done by payload versions before `2`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the key could not be hashed.
+    fn legacy_cipher(key: &[u8], salt: &[u8]) -> Result<XChaCha20Poly1305, EncryptError<F>> {
+        let mut hash = Zeroizing::new([0; 32]);
+
+        Argon2::default()
+            .hash_password_into(key, salt, &mut (*hash))
+            .map_err(EncryptError::Argon)?;
+
+        Ok(XChaCha20Poly1305::new(&(*hash).into()))
+    }
+
+    /// Decrypts the given salt-prefixed payload using the given associated data and the cipher
+    /// created from the payload's salt.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the payload is too short or could not be decrypted.
+    fn decrypt(
+        bytes: &[u8],
+        aad: &[u8],
+        cipher: impl FnOnce(&[u8]) -> Result<XChaCha20Poly1305, EncryptError<F>>,
+    ) -> Result<Vec<u8>, EncryptError<F>> {
+        if bytes.len() < SALT_LEN + NONCE_LEN + TAG_LEN {
+            return Err(Error::Io(std::io::ErrorKind::UnexpectedEof.into()).into());
+        }
+
+        let (salt, bytes) = bytes.split_at(SALT_LEN);
+        let (nonce, bytes) = bytes.split_at(NONCE_LEN);
+        let stream = DecryptorBE32::from_aead(cipher(salt)?, nonce.into());
+
+        stream.decrypt_last(Payload { msg: bytes, aad }).map_err(EncryptError::Encrypt)
+    }
+
+    /// Decrypts the given payload that does not contain a header, trying every key within the
+    /// given keyring.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the payload could not be decrypted by any key.
+    fn decrypt_legacy(keyring: &Keyring, bytes: &[u8]) -> Result<Vec<u8>, EncryptError<F>> {
+        // Older versions wrote the payload after a zeroed block as long as the salt, nonce, and
+        // encoded value combined, so the real payload must be located first.
+        let value_len = bytes.len().saturating_sub(2 * (SALT_LEN + NONCE_LEN) + TAG_LEN) / 2;
+        let prefix_len = SALT_LEN + NONCE_LEN + value_len;
+        let padded = bytes.len() == 2 * (SALT_LEN + NONCE_LEN) + TAG_LEN + 2 * value_len
+            && bytes[.. prefix_len].iter().all(|b| *b == 0);
+        let bytes = if padded { &bytes[prefix_len ..] } else { bytes };
+
+        let ids = std::iter::once(keyring.active())
+            .chain(keyring.ids().filter(|i| *i != keyring.active()));
+        let mut result = Err(EncryptError::MissingKey(keyring.active()));
+
+        for id in ids {
+            let Some(key) = keyring.get(id) else { continue };
+
+            result = Self::decrypt(bytes, &[], |salt| Self::legacy_cipher(key, salt));
+
+            if result.is_ok() {
+                break;
+            }
+        }
+
+        result
     }
 }
 
@@ -61,67 +444,163 @@ impl<F: Format> Format for Encrypt<F> {
     type EncodingError = EncryptError<F>;
     type DecodingError = EncryptError<F>;
 
+    const SELF_DESCRIBING: bool = F::SELF_DESCRIBING;
+
     fn extension(&self) -> String {
         self.inner().extension() + "z"
     }
 
     fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, Self::EncodingError> {
-        let password = Self::password()?;
-        let config = Argon2::default();
-
-        let mut salt = [0; 32];
-        let mut nonce = [0; 19];
-        let mut hash = [0; 32];
+        let keyring = self::keyring()?;
+        let id = keyring.active();
+        let master = keyring.master(id).map_err(EncryptError::Argon)?;
+        let master = master.ok_or(EncryptError::MissingKey(id))?;
 
-        OsRng.try_fill_bytes(&mut salt).map_err(EncryptError::Random)?;
-        OsRng.try_fill_bytes(&mut nonce).map_err(EncryptError::Random)?;
+        let mut salt = Zeroizing::new([0; SALT_LEN]);
+        let mut nonce = Zeroizing::new([0; NONCE_LEN]);
 
-        config.hash_password_into(&password, &salt, &mut hash).map_err(EncryptError::Argon)?;
+        OsRng.try_fill_bytes(&mut (*salt)).map_err(EncryptError::Random)?;
+        OsRng.try_fill_bytes(&mut (*nonce)).map_err(EncryptError::Random)?;
 
-        let cipher = XChaCha20Poly1305::new(&hash.into());
+        let bytes = Zeroizing::new(self.inner().encode(value).map_err(Error::Encoding)?);
+        let stream = EncryptorBE32::from_aead(Self::cipher(master, &(*salt))?, &(*nonce).into());
+        let mut buffer =
+            Vec::with_capacity(HEADER_LEN + SALT_LEN + NONCE_LEN + bytes.len() + TAG_LEN);
 
-        hash.zeroize();
+        buffer.extend_from_slice(&MAGIC);
+        buffer.push(VERSION);
+        buffer.extend_from_slice(&id.to_be_bytes());
+        buffer.extend_from_slice(&(*salt));
+        buffer.extend_from_slice(&(*nonce));
 
-        let bytes = self.inner().encode(value).map_err(Error::Encoding)?;
-        let stream = EncryptorBE32::from_aead(cipher, &nonce.into());
-        let mut buffer = vec![0u8; salt.len() + nonce.len() + bytes.len()];
-        let bytes = stream.encrypt_last(&(*bytes)).map_err(EncryptError::Encrypt)?;
+        // The header is authenticated alongside the value to prevent it from being altered.
+        let bytes = stream
+            .encrypt_last(Payload { msg: &bytes, aad: &buffer })
+            .map_err(EncryptError::Encrypt)?;
 
-        buffer.write_all(&salt).map_err(Error::from)?;
-        salt.zeroize();
-        buffer.write_all(&nonce).map_err(Error::from)?;
-        nonce.zeroize();
-        buffer.write_all(&bytes).map_err(Error::from)?;
+        buffer.extend_from_slice(&bytes);
 
         Ok(buffer)
     }
 
-    fn decode<T: for<'de> Deserialize<'de>>(
-        &self,
-        mut bytes: &[u8],
-    ) -> Result<T, Self::DecodingError> {
-        let mut salt = [0; 32];
-        let mut nonce = [0; 19];
+    fn decode<T: for<'de> Deserialize<'de>>(&self, bytes: &[u8]) -> Result<T, Self::DecodingError> {
+        let keyring = self::keyring()?;
 
-        bytes.read_exact(&mut salt).map_err(Error::from)?;
-        bytes.read_exact(&mut nonce).map_err(Error::from)?;
+        let bytes = Zeroizing::new(match Self::header(bytes) {
+            Some((version, id)) => {
+                let key = keyring.get(id).ok_or(EncryptError::MissingKey(id))?;
+                let aad = bytes.get(.. HEADER_LEN + SALT_LEN + NONCE_LEN).unwrap_or_default();
+                let decrypted = Self::decrypt(&bytes[HEADER_LEN ..], aad, |salt| {
+                    if version < VERSION {
+                        return Self::legacy_cipher(key, salt);
+                    }
 
-        let password = Self::password()?;
-        let config = Argon2::default();
-        let mut hash = [0; 32];
+                    let master = keyring.master(id).map_err(EncryptError::Argon)?;
 
-        config.hash_password_into(&password, &salt, &mut hash).map_err(EncryptError::Argon)?;
+                    Self::cipher(master.ok_or(EncryptError::MissingKey(id))?, salt)
+                });
 
-        let cipher = XChaCha20Poly1305::new(&hash.into());
+                // A legacy payload's salt could begin with a valid header by chance.
+                match decrypted {
+                    Ok(bytes) => bytes,
+                    Err(error) => Self::decrypt_legacy(&keyring, bytes).map_err(|_| error)?,
+                }
+            }
+            None => Self::decrypt_legacy(&keyring, bytes)?,
+        });
 
-        hash.zeroize();
+        self.inner().decode(&bytes).map_err(|e| Error::Decoding(e).into())
+    }
+}
 
-        let stream = DecryptorBE32::from_aead(cipher, &nonce.into());
-        let bytes = stream.decrypt_last(bytes).map_err(EncryptError::Encrypt)?;
+#[cfg(test)]
+mod tests {
+    use chacha20poly1305::aead::stream::EncryptorBE32;
+    use chacha20poly1305::aead::Payload;
 
-        salt.zeroize();
-        nonce.zeroize();
+    use super::{Encrypt, EncryptError, Keyring, KeyringError, NONCE_LEN, SALT_LEN};
+    use crate::tests::Plain;
+    use crate::Format;
 
-        self.inner().decode(&bytes).map_err(|e| Error::Decoding(e).into())
+    #[test]
+    fn encrypted_round_trip() -> Result<(), EncryptError<Plain>> {
+        let format = Encrypt::new(Plain);
+
+        super::install_keyring(Keyring::new(1, *b"first"));
+
+        let bytes = format.encode(&"value")?;
+
+        assert_eq!(Encrypt::<Plain>::key_id(&bytes), Some(1));
+        assert_eq!(format.decode::<String>(&bytes)?, "value");
+
+        // Values encrypted using a previous key remain readable once the active key changes.
+        super::install_keyring(Keyring::new(1, *b"first").with_active(2, *b"second"));
+
+        assert_eq!(format.decode::<String>(&bytes)?, "value");
+        assert_eq!(Encrypt::<Plain>::key_id(&format.encode(&"value")?), Some(2));
+
+        super::install_keyring(Keyring::new(2, *b"second"));
+
+        assert!(matches!(format.decode::<String>(&bytes), Err(EncryptError::MissingKey(1))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn master_keys_are_derived_once() -> Result<(), argon2::Error> {
+        let keyring = Keyring::new(1, *b"first");
+
+        let (first, again) = (keyring.master(1)?, keyring.master(1)?);
+
+        // The second call returns the cached key rather than deriving it again.
+        assert!(first.is_some_and(|f| again.is_some_and(|a| std::ptr::eq(f, a))));
+        assert!(keyring.master(2)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn decrypt_legacy_payloads() -> Result<(), EncryptError<Plain>> {
+        let keyring = Keyring::new(2, *b"second").with_key(1, *b"first");
+        let (salt, nonce) = ([1; SALT_LEN], [2; NONCE_LEN]);
+        let cipher = Encrypt::<Plain>::legacy_cipher(b"first", &salt)?;
+        let stream = EncryptorBE32::from_aead(cipher, &nonce.into());
+        let payload = stream
+            .encrypt_last(Payload { msg: b"\"value\"", aad: &[] })
+            .map_err(EncryptError::Encrypt)?;
+        let bytes = [&salt[..], &nonce, &payload].concat();
+
+        assert_eq!(Encrypt::<Plain>::decrypt_legacy(&keyring, &bytes)?, b"\"value\"");
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_keyring_entries() -> Result<(), KeyringError> {
+        let keyring = Keyring::parse("# comment\n\n 1 = first \n2=c2Vjb25k==\n")?;
+
+        assert_eq!(keyring.active(), 2);
+        assert_eq!(keyring.get(1), Some(&b"first"[..]));
+        assert_eq!(keyring.get(2), Some(&b"c2Vjb25k=="[..]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_keyring_without_identifier() -> Result<(), KeyringError> {
+        // The prefix is not a valid identifier, so the entire line is the key.
+        let keyring = Keyring::parse("abc=def==")?;
+
+        assert_eq!(keyring.active(), 0);
+        assert_eq!(keyring.get(0), Some(&b"abc=def=="[..]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_keyring_errors() {
+        assert!(matches!(Keyring::parse("1=a\n1=b"), Err(KeyringError::Duplicate(1))));
+        assert!(matches!(Keyring::parse("1=a\n2= "), Err(KeyringError::Invalid(2))));
+        assert!(matches!(Keyring::parse("# nothing"), Err(KeyringError::Empty)));
     }
 }
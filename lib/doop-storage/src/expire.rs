@@ -0,0 +1,148 @@
+use std::ffi::OsString;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::Backend;
+
+/// The file extension used for expiry files.
+pub const EXPIRY_EXTENSION: &str = "expires";
+
+/// Returns the hidden expiry file path for the given resource path.
+///
+/// Expiry files are named `.{file}.expires` and contain the resource's expiration time as a
+/// number of seconds since the Unix epoch.
+#[must_use]
+pub fn expiry_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".{EXPIRY_EXTENSION}"));
+
+    path.with_file_name(name)
+}
+
+/// Returns the resource path of the given expiry file path, or [`None`] if the path is not an
+/// expiry file.
+#[must_use]
+pub fn resource_path(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let name = name.strip_prefix('.')?.strip_suffix(EXPIRY_EXTENSION)?.strip_suffix('.')?;
+
+    (!name.is_empty()).then(|| path.with_file_name(name))
+}
+
+/// Returns whether the given path is an expiry file.
+#[must_use]
+pub fn is_expiry_file(path: &Path) -> bool {
+    self::resource_path(path).is_some()
+}
+
+/// Encodes the given expiration time.
+pub fn encode(time: SystemTime) -> Vec<u8> {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+
+    seconds.to_string().into_bytes()
+}
+
+/// Reads the expiration time stored within the given expiry file, returning [`None`] if it does
+/// not exist.
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be read or is invalid.
+pub fn read(backend: &dyn Backend, path: &Path) -> std::io::Result<Option<SystemTime>> {
+    let bytes = match backend.read(path) {
+        Ok(bytes) => bytes,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    let seconds = std::str::from_utf8(&bytes).ok().and_then(|s| s.trim().parse().ok());
+    let Some(seconds) = seconds else {
+        return Err(std::io::Error::new(ErrorKind::InvalidData, "invalid expiry file"));
+    };
+
+    Ok(Some(UNIX_EPOCH + Duration::from_secs(seconds)))
+}
+
+/// Removes the resource at the given path, returning the number of removed bytes.
+///
+/// Missing resources are treated as empty.
+///
+/// # Errors
+///
+/// This function will return an error if the resource could not be read or removed.
+fn remove(backend: &dyn Backend, path: &Path) -> std::io::Result<u64> {
+    let result = backend.read(path).and_then(|bytes| {
+        backend.remove(path)?;
+
+        Ok(bytes.len() as u64)
+    });
+
+    match result {
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(0),
+        result => result,
+    }
+}
+
+/// The result of sweeping expired resources from a storage backend.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Swept {
+    /// The number of expired resources that were removed.
+    pub removed: usize,
+    /// The total size of all removed files in bytes, including their expiry files.
+    pub bytes: u64,
+}
+
+/// Removes every expired resource within the installed storage [`backend`](<crate::backend>).
+///
+/// Each resource is locked while it is removed, so resources that are being updated are never
+/// removed mid-update. Expired resources remain readable until they are swept. Afterwards, every
+/// unused lock file whose resource no longer exists is removed, whether or not it was swept.
+///
+/// # Errors
+///
+/// This function will return an error if the backend could not be traversed, or if a resource
+/// could not be removed.
+pub fn sweep_expired() -> std::io::Result<Swept> {
+    let backend = crate::backend();
+    let now = SystemTime::now();
+    let mut swept = Swept::default();
+    let mut removed = vec![];
+
+    for path in crate::backend::files(&(*backend))? {
+        let Some(resource) = self::resource_path(&path) else {
+            continue;
+        };
+
+        // The expiry is read again while locked, as it may have been extended in the meantime.
+        let _lock = backend.lock(&resource)?;
+        let Some(expires) = self::read(&(*backend), &path)? else {
+            continue;
+        };
+
+        if expires > now {
+            continue;
+        }
+
+        let _barrier = crate::lock::Barrier::shared(&(*backend))?;
+
+        swept.bytes += self::remove(&(*backend), &resource)?;
+        swept.bytes += self::remove(&(*backend), &path)?;
+        swept.removed += 1;
+
+        removed.push(resource.into_boxed_path());
+    }
+
+    let barrier = crate::lock::Barrier::shared(&(*backend))?;
+
+    crate::quota::forget(&(*backend), &removed)?;
+
+    drop(barrier);
+
+    // The locks of the removed resources are released by now, so their lock files are included.
+    backend.sweep_locks()?;
+
+    Ok(swept)
+}
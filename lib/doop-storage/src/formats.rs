@@ -3,9 +3,11 @@ use serde::{Deserialize, Serialize};
 use crate::Format;
 
 /// The [MessagePack](https://msgpack.io/index.html) data format.
+#[cfg(feature = "formats")]
 #[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct MsgPack;
 
+#[cfg(feature = "formats")]
 impl Format for MsgPack {
     type EncodingError = rmp_serde::encode::Error;
     type DecodingError = rmp_serde::decode::Error;
@@ -24,9 +26,11 @@ impl Format for MsgPack {
 }
 
 /// The [JSON](https://www.json.org/json-en.html) data format.
+#[cfg(feature = "formats")]
 #[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Json;
 
+#[cfg(feature = "formats")]
 impl Format for Json {
     type EncodingError = serde_json::Error;
     type DecodingError = serde_json::Error;
@@ -45,9 +49,11 @@ impl Format for Json {
 }
 
 /// The [TOML](https://toml.io/en/) data format.
+#[cfg(feature = "formats")]
 #[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Toml;
 
+#[cfg(feature = "formats")]
 impl Format for Toml {
     type EncodingError = toml::ser::Error;
     type DecodingError = toml::de::Error;
@@ -64,3 +70,82 @@ impl Format for Toml {
         toml::from_str(&String::from_utf8_lossy(bytes))
     }
 }
+
+/// The [CBOR](https://cbor.io/) data format.
+#[cfg(feature = "cbor")]
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Cbor;
+
+#[cfg(feature = "cbor")]
+impl Format for Cbor {
+    type EncodingError = ciborium::ser::Error<std::io::Error>;
+    type DecodingError = ciborium::de::Error<std::io::Error>;
+
+    fn extension(&self) -> String {
+        "cbor".to_string()
+    }
+
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, Self::EncodingError> {
+        let mut bytes = vec![];
+
+        ciborium::into_writer(value, &mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    fn decode<T: for<'de> Deserialize<'de>>(&self, bytes: &[u8]) -> Result<T, Self::DecodingError> {
+        ciborium::from_reader(bytes)
+    }
+}
+
+/// The [RON](https://github.com/ron-rs/ron) data format.
+#[cfg(feature = "ron")]
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Ron;
+
+#[cfg(feature = "ron")]
+impl Format for Ron {
+    type EncodingError = ron::Error;
+    type DecodingError = ron::error::SpannedError;
+
+    fn extension(&self) -> String {
+        "ron".to_string()
+    }
+
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, Self::EncodingError> {
+        ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()).map(String::into_bytes)
+    }
+
+    fn decode<T: for<'de> Deserialize<'de>>(&self, bytes: &[u8]) -> Result<T, Self::DecodingError> {
+        ron::de::from_bytes(bytes)
+    }
+}
+
+/// The [bincode](https://github.com/bincode-org/bincode) data format.
+///
+/// Bincode is not self-describing, so values cannot be decoded without knowing their type. As
+/// migrations decode older values as a [`serde_json::Value`], this format cannot be used by a
+/// versioned [`Key`](<crate::Key>).
+#[cfg(feature = "bincode")]
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Bincode;
+
+#[cfg(feature = "bincode")]
+impl Format for Bincode {
+    type EncodingError = bincode::Error;
+    type DecodingError = bincode::Error;
+
+    const SELF_DESCRIBING: bool = false;
+
+    fn extension(&self) -> String {
+        "bin".to_string()
+    }
+
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, Self::EncodingError> {
+        bincode::serialize(value)
+    }
+
+    fn decode<T: for<'de> Deserialize<'de>>(&self, bytes: &[u8]) -> Result<T, Self::DecodingError> {
+        bincode::deserialize(bytes)
+    }
+}
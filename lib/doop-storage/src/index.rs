@@ -0,0 +1,447 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Debug, Formatter};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::{Backend, Error, Listing, Location, Lock, Stored};
+
+/// The file extension used for index files.
+pub const INDEX_EXTENSION: &str = "index";
+
+/// The contents of an index file, mapping each term to the locations of its resources.
+type Entries = BTreeMap<Box<str>, BTreeSet<Box<str>>>;
+
+/// A function that returns the indexed term of a value.
+pub type Term<T> = fn(&T) -> serde_json::Result<String>;
+
+/// A secondary index over the values of a stored type.
+///
+/// An index maps each value's term, such as the value of one of its fields, to the paths of every
+/// resource with that term. Indexes are kept within hidden files named `.{name}.index`, and are
+/// updated whenever a value is written or removed through a key.
+///
+/// If the type's location has a directory that is filled by its leading argument, such as a guild
+/// identifier, each such directory has its own index file that only lists the resources within it.
+/// Otherwise, a single index file is placed within the leading literal directories of the type's
+/// location.
+pub struct Index<T> {
+    /// The location template of the indexed type.
+    location: &'static str,
+    /// The index's name.
+    name: &'static str,
+    /// Returns the indexed term of a value.
+    term: Term<T>,
+}
+
+impl<T> Index<T> {
+    /// Creates a new [`Index<T>`] for the type stored at the given location template.
+    ///
+    /// Types that share the same leading literal directories must use distinct index names.
+    #[must_use]
+    pub const fn new(location: &'static str, name: &'static str, term: Term<T>) -> Self {
+        Self { location, name, term }
+    }
+
+    /// Returns the name of this [`Index<T>`].
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the path of this [`Index<T>`]'s file within the given directory.
+    fn file(&self, dir: &Path) -> PathBuf {
+        dir.join(format!(".{}.{INDEX_EXTENSION}", self.name))
+    }
+
+    /// Returns the path of the index file that lists the resource at the given path, relative to
+    /// the storage root.
+    #[must_use]
+    pub fn path_of(&self, path: &Path) -> PathBuf {
+        let location = Location::new(self.location);
+        let depth = location.scope_depth();
+
+        self.file(&depth.map_or_else(|| location.prefix(), |d| path.components().take(d).collect()))
+    }
+
+    /// Returns the path of the index file that lists every resource whose leading argument is the
+    /// given escaped argument, relative to the storage root.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the argument is not a valid path component.
+    pub fn path_within(&self, leading: &str) -> std::io::Result<PathBuf> {
+        let location = Location::new(self.location);
+
+        if location.scope_depth().is_none() {
+            return Ok(self.file(&location.prefix()));
+        }
+
+        Ok(self.file(&location.prefix().join(crate::backend::from_location(leading)?)))
+    }
+
+    /// Returns the path of every index file of this [`Index<T>`] within the given backend,
+    /// relative to the storage root.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the backend could not be traversed.
+    pub fn paths(&self, backend: &dyn Backend) -> std::io::Result<Vec<PathBuf>> {
+        let location = Location::new(self.location);
+        let prefix = location.prefix();
+
+        if location.scope_depth().is_none() {
+            return Ok(vec![self.file(&prefix)]);
+        }
+
+        let dirs = backend.entries(&prefix)?.into_iter().filter(|e| e.is_dir);
+
+        Ok(dirs.map(|e| self.file(&e.path)).collect())
+    }
+
+    /// Returns the path of the single index file that was used before index files were kept
+    /// within each leading argument's directory, if this [`Index<T>`] no longer uses it.
+    fn legacy_path(&self) -> Option<PathBuf> {
+        let location = Location::new(self.location);
+
+        location.scope_depth().map(|_| self.file(&location.prefix()))
+    }
+
+    /// Returns the indexed term of the given value.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the term could not be encoded.
+    pub fn term(&self, value: &T) -> std::io::Result<String> {
+        (self.term)(value).map_err(Into::into)
+    }
+}
+
+impl<T> Debug for Index<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Term functions are function pointers, which are not meaningful to print.
+        f.debug_struct("Index")
+            .field("location", &self.location)
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns whether the given path is an index file.
+#[must_use]
+pub fn is_index_file(path: &Path) -> bool {
+    let hidden = path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.'));
+
+    hidden && path.extension().is_some_and(|e| e == INDEX_EXTENSION)
+}
+
+/// Reads the index file at the given path, returning an empty index if it does not exist.
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be read or is invalid.
+fn read(backend: &dyn Backend, path: &Path) -> std::io::Result<Entries> {
+    match backend.read(path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).map_err(Into::into),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(Entries::new()),
+        Err(error) => Err(error),
+    }
+}
+
+/// Writes the given entries into the index file at the given path, removing it if it is empty.
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be written or removed.
+fn write(backend: &dyn Backend, path: &Path, entries: &Entries) -> std::io::Result<()> {
+    if !entries.is_empty() {
+        return backend.write(path, &serde_json::to_vec(entries)?);
+    }
+
+    match backend.remove(path) {
+        Err(error) if error.kind() != ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+/// Acquires the locks of every index file that lists any of the resources at the given paths.
+///
+/// The locks must be held while the resources are written or removed and their indexes are
+/// updated, so that concurrent writes cannot leave an index out of step with its resources.
+///
+/// # Errors
+///
+/// This function will return an error if any lock could not be acquired.
+pub fn lock<T>(
+    backend: &dyn Backend,
+    indexes: &[Index<T>],
+    paths: &[&Path],
+) -> std::io::Result<Vec<Lock>> {
+    let files = paths.iter().flat_map(|p| indexes.iter().map(|i| i.path_of(p)));
+
+    // Locks are always acquired in the same order to prevent deadlocks between callers.
+    files.collect::<BTreeSet<_>>().iter().map(|f| backend.lock(f)).collect()
+}
+
+/// Updates the given indexes to reflect that the resource at the given path now contains the
+/// given value, or was removed if no value is given.
+///
+/// Index files are only written if they have changed. The caller must hold the storage barrier and
+/// the index file [locks](<lock>).
+///
+/// # Errors
+///
+/// This function will return an error if any index file could not be read or written.
+pub fn update<T>(
+    backend: &dyn Backend,
+    indexes: &[Index<T>],
+    path: &Path,
+    value: Option<&T>,
+) -> std::io::Result<()> {
+    if indexes.is_empty() {
+        return Ok(());
+    }
+
+    let location = crate::backend::location(path)?.into_boxed_str();
+
+    for index in indexes {
+        let term = value.map(|v| index.term(v)).transpose()?;
+        let file = index.path_of(path);
+        let mut entries = self::read(backend, &file)?;
+        let previous = entries.clone();
+
+        entries.retain(|t, paths| {
+            if term.as_deref() != Some(&(**t)) {
+                paths.remove(&location);
+            }
+
+            !paths.is_empty()
+        });
+
+        if let Some(term) = term {
+            entries.entry(term.into_boxed_str()).or_default().insert(location.clone());
+        }
+        if entries != previous {
+            self::write(backend, &file, &entries)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the named index of the given [`Stored`] type.
+///
+/// # Errors
+///
+/// This function will return an error if the type does not have the named index.
+fn find<T: Stored>(indexes: &[Index<T>], name: &str) -> std::io::Result<usize> {
+    let error = || std::io::Error::new(ErrorKind::NotFound, format!("unknown index '{name}'"));
+
+    indexes.iter().position(|i| i.name() == name).ok_or_else(error)
+}
+
+/// Lists every stored resource of the given [`Stored`] type that is listed under the given term
+/// within any of the given index files, and whose leading argument is the given escaped argument
+/// if present.
+///
+/// # Errors
+///
+/// This function will return an error if the term could not be encoded, or if any index file
+/// could not be read.
+fn search<T>(
+    files: &[PathBuf],
+    leading: Option<&str>,
+    value: &impl Serialize,
+) -> Result<Listing<T>, Error<T::Format>>
+where
+    T: Stored,
+    T::Arguments: Clone,
+{
+    let backend = crate::backend();
+    let term = serde_json::to_string(value).map_err(std::io::Error::from)?;
+    let template = Location::new(T::LOCATION);
+    let mut list = vec![];
+
+    for file in files {
+        let mut entries = self::read(&(*backend), file)?;
+
+        for location in entries.remove(&(*term)).unwrap_or_default() {
+            let path = crate::backend::from_location(&location)?;
+            let stripped = crate::backend::location(&path.with_extension(""))?;
+            let Some(captures) = template.capture(&stripped) else {
+                continue;
+            };
+
+            if leading.is_some_and(|l| captures.first().is_none_or(|c| &(**c) != l)) {
+                continue;
+            }
+
+            let components = captures.iter().map(|c| &(**c)).collect::<Box<[_]>>();
+            let Some(arguments) = T::arguments(&components) else {
+                continue;
+            };
+            let key = T::stored(arguments.clone());
+
+            // This ensures that the file's extension matches the expected format.
+            if key.path() == &(*path) && key.exists()? {
+                list.push((arguments, key));
+            }
+        }
+    }
+
+    list.sort_unstable_by(|(_, a), (_, b)| a.path().cmp(b.path()));
+
+    Ok(list)
+}
+
+/// Lists every stored resource of the given [`Stored`] type whose term within the given index
+/// matches the given value.
+///
+/// Entries are sorted by their file path, and resources that no longer exist are skipped.
+///
+/// # Errors
+///
+/// This function will return an error if the index does not exist, if the term could not be
+/// encoded, or if any index file could not be read.
+pub fn query<T>(name: &str, value: &impl Serialize) -> Result<Listing<T>, Error<T::Format>>
+where
+    T: Stored,
+    T::Arguments: Clone,
+{
+    let indexes = T::indexes();
+    let index = &indexes[self::find(&indexes, name)?];
+
+    self::search(&index.paths(&(*crate::backend()))?, None, value)
+}
+
+/// Lists every stored resource of the given [`Stored`] type whose leading argument is the given
+/// escaped argument, and whose term within the given index matches the given value.
+///
+/// Only the index file of the leading argument is read if the type has one.
+///
+/// # Errors
+///
+/// This function will return an error if the index does not exist, if the term could not be
+/// encoded, or if the index file could not be read.
+pub fn query_within<T>(
+    leading: &str,
+    name: &str,
+    value: &impl Serialize,
+) -> Result<Listing<T>, Error<T::Format>>
+where
+    T: Stored,
+    T::Arguments: Clone,
+{
+    let indexes = T::indexes();
+    let index = &indexes[self::find(&indexes, name)?];
+
+    self::search(&[index.path_within(leading)?], Some(leading), value)
+}
+
+/// Returns whether any index of the given [`Stored`] type is missing or uses an outdated layout,
+/// and must be [rebuilt](<rebuild>).
+///
+/// This only traverses the backend, without reading any resources.
+///
+/// # Errors
+///
+/// This function will return an error if the storage backend could not be traversed.
+pub fn is_stale<T: Stored>() -> std::io::Result<bool> {
+    let backend = crate::backend();
+    let location = Location::new(T::LOCATION);
+
+    for index in T::indexes().iter() {
+        if let Some(legacy) = index.legacy_path() {
+            if backend.exists(&legacy)? {
+                return Ok(true);
+            }
+        }
+
+        // Every resource is listed within its index file, so any scope that contains resources
+        // but does not have an index file is missing it.
+        if location.scope_depth().is_none() {
+            let missing = !backend.exists(&index.path_within("")?)?;
+
+            if missing && !location.walk(&(*backend))?.is_empty() {
+                return Ok(true);
+            }
+
+            continue;
+        }
+
+        for file in index.paths(&(*backend))? {
+            let Some(scope) = file.parent().and_then(Path::file_name).and_then(|n| n.to_str())
+            else {
+                continue;
+            };
+
+            if !backend.exists(&file)? && !location.walk_within(&(*backend), &[scope])?.is_empty() {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Rebuilds every index of the given [`Stored`] type from its stored resources, returning the
+/// number of indexed resources.
+///
+/// Resources that could not be read are left out of every index, and are never migrated. Values
+/// that are written while the indexes are being rebuilt may be left out of the rebuilt indexes.
+///
+/// # Errors
+///
+/// This function will return an error if the storage backend could not be traversed, or if any
+/// index file could not be written.
+pub fn rebuild<T>() -> Result<usize, Error<T::Format>>
+where
+    T: Stored,
+    T::Arguments: Clone,
+{
+    let indexes = T::indexes();
+
+    if indexes.is_empty() {
+        return Ok(0);
+    }
+
+    let backend = crate::backend();
+    let mut rebuilt = vec![BTreeMap::<PathBuf, Entries>::new(); indexes.len()];
+    let mut count = 0;
+
+    for (_, key) in T::list()? {
+        // Values are decoded directly, as reading them through the key could write them back.
+        let bytes = backend.read(key.path()).map_err(Error::Io);
+        let Ok((value, _)) = bytes.and_then(|b| key.decode(&b)) else {
+            continue;
+        };
+        let location = crate::backend::location(key.path())?.into_boxed_str();
+
+        for (index, files) in indexes.iter().zip(&mut rebuilt) {
+            let term = index.term(&value)?.into_boxed_str();
+            let entries = files.entry(index.path_of(key.path())).or_default();
+
+            entries.entry(term).or_default().insert(location.clone());
+        }
+
+        count += 1;
+    }
+
+    for (index, mut files) in indexes.iter().zip(rebuilt) {
+        // Index files of scopes without any resources, and any outdated index file, are removed.
+        for file in index.paths(&(*backend))?.into_iter().chain(index.legacy_path()) {
+            files.entry(file).or_default();
+        }
+
+        for (file, entries) in &files {
+            // Index locks are always acquired before the barrier, matching writes.
+            let _lock = backend.lock(file)?;
+            let _barrier = crate::lock::Barrier::shared(&(*backend))?;
+
+            self::write(&(*backend), file, entries)?;
+        }
+    }
+
+    Ok(count)
+}
@@ -3,44 +3,208 @@
 #![warn(clippy::nursery, clippy::todo, clippy::pedantic, missing_docs)]
 #![allow(clippy::module_name_repetitions)]
 
+use std::cell::RefCell;
 use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
+use std::io::ErrorKind;
 use std::marker::PhantomData;
 use std::path::Path;
-use std::sync::OnceLock;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, SystemTime};
 
 use serde::{Deserialize, Serialize};
 
-/// Stores the preferred storage directory.
-static DIRECTORY: OnceLock<Box<Path>> = OnceLock::new();
+use crate::backend::Installed;
+use crate::lock::Barrier;
+use crate::quota::Ledger;
+use crate::schema::Envelope;
 
-/// Returns a reference to the preferred storage directory's path.
+/// Stores the globally installed storage backend.
+static BACKEND: RwLock<Option<Arc<dyn Backend>>> = RwLock::new(None);
+
+/// Prevents keys from being written while it is exclusively held, such as while a snapshot is
+/// being captured. This is only held through a [`Barrier`].
+static BARRIER: RwLock<()> = RwLock::new(());
+
+thread_local! {
+    /// Stores the storage backend installed for the current thread, which takes precedence over
+    /// the global backend.
+    static LOCAL_BACKEND: RefCell<Option<Arc<dyn Backend>>> = const { RefCell::new(None) };
+}
+
+/// Returns the storage backend that new keys are created within.
+///
+/// A backend installed for the current thread through [`install_local`] or a [`ScopedBackend`]
+/// takes precedence over the global backend.
 ///
 /// # Panics
 ///
-/// Panics if the directory has not been set.
+/// Panics if no backend has been installed.
 #[allow(clippy::expect_used)]
-pub fn directory() -> &'static Path {
-    DIRECTORY.get().map(|p| &**p).expect("the storage directory has not been set")
+#[must_use]
+pub fn backend() -> Arc<dyn Backend> {
+    LOCAL_BACKEND
+        .with_borrow(Clone::clone)
+        .or_else(|| BACKEND.read().unwrap_or_else(PoisonError::into_inner).clone())
+        .expect("the storage backend has not been installed")
 }
 
-/// Initializes the preferred storage directory.
+/// Installs a [`FileSystem`] backend within the given directory as the global storage backend.
 ///
-/// # Panics
+/// This also removes any temporary files that were left behind by interrupted writes, returning
+/// the paths of every removed file.
 ///
-/// Panics if the directory has already been set.
-#[allow(clippy::expect_used)]
-pub fn install(dir: impl AsRef<Path>) {
-    DIRECTORY.set(dir.as_ref().into()).expect("the storage directory has already been set");
+/// # Errors
+///
+/// This function will return an error if the directory could not be recovered.
+pub fn install(dir: impl AsRef<Path>) -> std::io::Result<Box<[Box<Path>]>> {
+    self::install_backend(FileSystem::new(dir))
+}
+
+/// Installs the given backend as the global storage backend, replacing any previous backend.
+///
+/// Keys that were created before the backend was replaced continue to use the previous backend.
+/// This also recovers the backend from any interrupted writes, returning the paths of every
+/// removed file.
+///
+/// # Errors
+///
+/// This function will return an error if the backend could not be recovered.
+pub fn install_backend(backend: impl Backend + 'static) -> std::io::Result<Box<[Box<Path>]>> {
+    let recovered = backend.recover()?;
+
+    let backend = Arc::new(Installed::new(backend));
+
+    *BACKEND.write().unwrap_or_else(PoisonError::into_inner) = Some(backend);
+
+    Ok(recovered)
+}
+
+/// Installs the given backend as the storage backend of the current thread until the returned
+/// guard is dropped.
+///
+/// This is intended for tests, where each test may use its own [`Memory`] backend without
+/// affecting other tests. Use a [`ScopedBackend`] if the backend must be shared with other
+/// threads.
+#[must_use = "the backend is uninstalled when the guard is dropped"]
+pub fn install_local(backend: impl Backend + 'static) -> LocalBackend {
+    ScopedBackend::new(backend).enter()
+}
+
+/// A storage backend that may be installed for any number of threads, each until its returned
+/// guard is dropped.
+///
+/// Every thread that enters the same [`ScopedBackend`] shares a single installed backend, so
+/// caches and watchers treat their resources as the same resources.
+///
+/// ```
+/// # use doop_storage::{Memory, ScopedBackend};
+/// let scope = ScopedBackend::new(Memory::new());
+/// let _guard = scope.enter();
+///
+/// std::thread::scope(|s| {
+///     s.spawn(|| {
+///         let _guard = scope.enter();
+///
+///         // Keys created here use the same backend as the spawning thread.
+///     });
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct ScopedBackend {
+    /// The installed backend.
+    backend: Arc<dyn Backend>,
 }
 
+impl ScopedBackend {
+    /// Installs the given backend, without entering it on any thread.
+    pub fn new(backend: impl Backend + 'static) -> Self {
+        Self { backend: Arc::new(Installed::new(backend)) }
+    }
+
+    /// Installs the given backend with its own storage [`Quota`], without entering it on any
+    /// thread.
+    ///
+    /// The quota is enforced instead of the globally installed quota for keys that use this
+    /// backend.
+    pub fn with_quota(backend: impl Backend + 'static, quota: Quota) -> Self {
+        Self { backend: Arc::new(Installed::new(backend).with_quota(quota)) }
+    }
+
+    /// Returns the installed backend.
+    #[must_use]
+    pub const fn backend(&self) -> &Arc<dyn Backend> {
+        &self.backend
+    }
+
+    /// Installs this backend as the storage backend of the current thread until the returned
+    /// guard is dropped.
+    #[must_use = "the backend is uninstalled when the guard is dropped"]
+    pub fn enter(&self) -> LocalBackend {
+        let previous = LOCAL_BACKEND.replace(Some(Arc::clone(&self.backend)));
+
+        LocalBackend { previous, _marker: PhantomData }
+    }
+}
+
+/// Restores the previous storage backend of the current thread when dropped.
+#[derive(Debug)]
+pub struct LocalBackend {
+    /// The previously installed backend.
+    previous: Option<Arc<dyn Backend>>,
+    /// Ensures that the guard is dropped on the thread that created it.
+    _marker: PhantomData<*const ()>,
+}
+
+impl Drop for LocalBackend {
+    fn drop(&mut self) {
+        LOCAL_BACKEND.set(self.previous.take());
+    }
+}
+
+pub use serde_json;
+
+pub use crate::backend::{Backend, Entry, FileSystem, Memory};
+pub use crate::cache::{Cache, CacheStats, Cached};
+pub use crate::expire::{sweep_expired, Swept};
+pub use crate::index::{Index, Term};
+pub use crate::location::{Captured, Location};
+pub use crate::lock::{Lock, LockSet};
+pub use crate::quota::{install_quota, quota, recount, usage, Quota, Usage};
+pub use crate::schema::{Migration, Schema};
+pub use crate::verify::{Issue, Registry, Report};
+pub use crate::watch::{Change, Event, Watcher};
+
+mod atomic;
+mod backend;
+mod cache;
+mod expire;
+mod index;
+mod location;
+mod lock;
+mod quota;
+mod schema;
+mod verify;
+mod watch;
+
+#[cfg(feature = "async")] mod task;
+
 #[cfg(feature = "compress")] pub use crate::compress::*;
 #[cfg(feature = "compress")] mod compress;
 
 #[cfg(feature = "encrypt")] pub use crate::encrypt::*;
 #[cfg(feature = "encrypt")] mod encrypt;
 
-#[cfg(feature = "formats")] pub use crate::formats::*;
-#[cfg(feature = "formats")] mod formats;
+#[cfg(any(feature = "bincode", feature = "cbor", feature = "formats", feature = "ron"))]
+pub use crate::formats::*;
+#[cfg(any(feature = "bincode", feature = "cbor", feature = "formats", feature = "ron"))]
+mod formats;
+
+#[cfg(feature = "snapshot")] pub use crate::snapshot::*;
+#[cfg(feature = "snapshot")] mod snapshot;
+
+#[cfg(feature = "sqlite")] pub use crate::sqlite::*;
+#[cfg(feature = "sqlite")] mod sqlite;
 
 /// A possible error.
 #[derive(Debug, thiserror::Error)]
@@ -54,6 +218,12 @@ pub enum Error<F: Format> {
     /// A [`Format`] decoding error.
     #[error("{0}")]
     Decoding(F::DecodingError),
+    /// A stored value's schema version is newer than the supported version.
+    #[error("unsupported schema version {0}")]
+    Version(u32),
+    /// A write would exceed the storage [`Quota`] of the given namespace.
+    #[error("storage quota exceeded within '{0}' ({1})")]
+    Quota(Box<str>, Usage),
 }
 
 /// A data storage [`Format`] used within the storage system.
@@ -72,6 +242,12 @@ pub trait Format: Debug {
     /// The type returned in the event of an error during decoding.
     type DecodingError: Debug + Display;
 
+    /// Whether values can be decoded without knowing their type.
+    ///
+    /// Versioned values are migrated as [`serde_json::Value`]s, so only self-describing formats
+    /// may be used by a [`Key<T, F>`] with a [`Schema`].
+    const SELF_DESCRIBING: bool = true;
+
     /// Returns the extension for this [`Format`].
     fn extension(&self) -> String;
 
@@ -90,17 +266,29 @@ pub trait Format: Debug {
     fn decode<T: for<'de> Deserialize<'de>>(&self, bytes: &[u8]) -> Result<T, Self::DecodingError>;
 }
 
-/// Describes and represents a resource entry within the filesystem.
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+/// Describes and represents a resource entry within a storage [`Backend`].
+#[derive(Clone, Debug)]
 pub struct Key<T, F>
 where
     T: Serialize + for<'de> Deserialize<'de>,
     F: Format,
 {
-    /// The value's filepath.
+    /// The value's path, relative to the backend's storage root.
     path: Box<Path>,
+    /// The value's storage backend.
+    backend: Arc<dyn Backend>,
     /// The value's format.
     format: F,
+    /// The value's schema, if it is versioned.
+    schema: Option<Schema>,
+    /// The value's cache, if it is cached.
+    cache: Option<Arc<dyn Cached<T>>>,
+    /// The value's secondary indexes.
+    indexes: Arc<[Index<T>]>,
+    /// The value's quota namespace, if its usage is tracked.
+    namespace: Option<Box<str>>,
+    /// The duration after which the value expires once written, if it expires.
+    ttl: Option<Duration>,
     /// Type marker.
     _marker: PhantomData<fn() -> T>,
 }
@@ -110,11 +298,32 @@ where
     T: Serialize + for<'de> Deserialize<'de>,
     F: Format,
 {
-    /// Creates a new [`Key<T, F>`].
+    /// Creates a new [`Key<T, F>`] within the current storage [`backend`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if no backend has been installed.
     pub fn new(path: impl AsRef<Path>, format: F) -> Self {
-        let path = directory().join(path).with_extension(format.extension()).into_boxed_path();
+        Self::new_within(crate::backend(), path, format)
+    }
 
-        Self { path, format, _marker: PhantomData }
+    /// Creates a new [`Key<T, F>`] within the given storage backend.
+    pub fn new_within(backend: Arc<dyn Backend>, path: impl AsRef<Path>, format: F) -> Self {
+        let path = path.as_ref().with_extension(format.extension()).into_boxed_path();
+
+        let indexes = Arc::new([]);
+
+        Self {
+            path,
+            backend,
+            format,
+            schema: None,
+            cache: None,
+            indexes,
+            namespace: None,
+            ttl: None,
+            _marker: PhantomData,
+        }
     }
 
     /// Creates a new [`Key<T, F>`] with a defaulted format.
@@ -125,23 +334,297 @@ where
         Self::new(path, F::default())
     }
 
+    /// Returns this [`Key<T, F>`] with the given [`Schema`].
+    ///
+    /// Values of a versioned key are stored alongside their schema version, and older values are
+    /// migrated to the current version when read.
+    ///
+    /// This fails to compile if the key's [`Format`] is not self-describing.
+    #[must_use]
+    pub const fn with_schema(mut self, schema: Schema) -> Self {
+        const {
+            assert!(F::SELF_DESCRIBING, "versioned values require a self-describing format");
+        };
+
+        self.schema = Some(schema);
+
+        self
+    }
+
+    /// Returns this [`Key<T, F>`] with the given [`Cache<T>`].
+    ///
+    /// Reads are served from the cache when possible, and the cached value is invalidated
+    /// whenever it is written or removed through any key that shares the cache. The cache is only
+    /// used while the key's resource is watched by a [`Watcher<T>`], or once it has been written
+    /// within this process, as changes made outside of this process are otherwise not observed.
+    #[must_use]
+    pub fn with_cache(mut self, cache: Arc<Cache<T>>) -> Self
+    where
+        T: Clone + Send + 'static,
+    {
+        self.cache = Some(cache);
+
+        self
+    }
+
+    /// Returns this [`Key<T, F>`] with the given secondary [`Index<T>`] list.
+    ///
+    /// Every index is updated whenever a value is written or removed through this key.
+    #[must_use]
+    pub fn with_indexes(mut self, indexes: Arc<[Index<T>]>) -> Self {
+        self.indexes = indexes;
+
+        self
+    }
+
+    /// Returns this [`Key<T, F>`] within the given quota namespace, such as a guild identifier.
+    ///
+    /// The size of every value written through this key is tracked within the namespace's usage,
+    /// and writes that would exceed the installed [`Quota`] fail with [`Error::Quota`].
+    #[must_use]
+    pub fn with_namespace(mut self, namespace: impl Into<Box<str>>) -> Self {
+        self.namespace = Some(namespace.into());
+
+        self
+    }
+
+    /// Returns this [`Key<T, F>`] with the given time-to-live.
+    ///
+    /// Every value written through this key expires after the given duration, as if it were
+    /// written using [`write_expiring`](<Key::write_expiring>).
+    #[must_use]
+    pub const fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+
+        self
+    }
+
+    /// Returns the quota namespace of this [`Key<T, F>`], if its usage is tracked.
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    /// Records the given size of this [`Key<T, F>`]'s associated resource within its namespace's
+    /// usage ledger, or its removal if no size is given, returning the unsaved ledger.
+    ///
+    /// The caller must hold the storage barrier, and must save the ledger once the resource has
+    /// been written or removed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the ledger could not be read, or if the change would
+    /// exceed the installed [`Quota`].
+    fn account(&self, bytes: Option<u64>) -> Result<Option<Ledger<'_>>, Error<F>> {
+        let Some(namespace) = &self.namespace else {
+            return Ok(None);
+        };
+        let mut ledger = Ledger::open(&(*self.backend), namespace)?;
+
+        ledger.set(&self.path, bytes)?;
+
+        if ledger.allowed_by(&self.quota()) {
+            Ok(Some(ledger))
+        } else {
+            Err(Error::Quota(namespace.clone(), ledger.usage()))
+        }
+    }
+
+    /// Returns the storage [`Quota`] enforced for this [`Key<T, F>`]'s backend.
+    fn quota(&self) -> Quota {
+        self.backend.quota().unwrap_or_else(crate::quota)
+    }
+
+    /// Records the given encoded sizes of the given keys' associated resources within their
+    /// namespaces' usage ledgers, returning the unsaved ledgers.
+    ///
+    /// The keys must share a backend. The caller must hold the storage barrier, and must save the
+    /// ledgers once the resources have been written.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any ledger could not be read, or if the changes would
+    /// exceed the installed [`Quota`].
+    fn account_all<'k>(
+        keys: &[&'k Self],
+        encoded: &[Vec<u8>],
+    ) -> Result<Vec<Ledger<'k>>, Error<F>> {
+        // Ledgers are always locked in the same order to prevent deadlocks between callers.
+        let mut namespaces = keys.iter().filter_map(|k| k.namespace()).collect::<Box<[_]>>();
+
+        namespaces.sort_unstable();
+
+        let quota = keys[0].quota();
+        let mut ledgers = Vec::with_capacity(namespaces.len());
+
+        for (index, namespace) in namespaces.iter().enumerate() {
+            if index > 0 && namespaces[index - 1] == *namespace {
+                continue;
+            }
+
+            let mut ledger = Ledger::open(&(*keys[0].backend), namespace)?;
+
+            for (key, bytes) in keys.iter().zip(encoded) {
+                if key.namespace() == Some(namespace) {
+                    ledger.set(&key.path, Some(bytes.len() as u64))?;
+                }
+            }
+
+            if !ledger.allowed_by(&quota) {
+                return Err(Error::Quota((*namespace).into(), ledger.usage()));
+            }
+
+            ledgers.push(ledger);
+        }
+
+        Ok(ledgers)
+    }
+
+    /// Invalidates the cached value of this [`Key<T, F>`], if it is cached.
+    ///
+    /// If the resource was written, it may be cached from now on even while it is not watched.
+    fn invalidate(&self, written: bool) {
+        match &self.cache {
+            Some(cache) if written => cache.written(&self.backend, &self.path),
+            Some(cache) => cache.remove(&self.backend, &self.path),
+            None => {}
+        }
+    }
+
+    /// Returns a reference to the path of this [`Key<T, F>`], relative to its backend's storage
+    /// root.
+    pub const fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns a reference to the storage backend of this [`Key<T, F>`].
+    pub const fn backend(&self) -> &Arc<dyn Backend> {
+        &self.backend
+    }
+
     /// Returns whether this [`Key<T, F>`] exists within the storage system.
     ///
     /// # Errors
     ///
     /// This function will return an error if the path could not be verified.
     pub fn exists(&self) -> Result<bool, Error<F>> {
-        self.path.try_exists().map_err(Into::into)
+        self.backend.exists(&self.path).map_err(Into::into)
     }
 
-    /// Reads this [`Key<T, F>`]'s associated resource.
+    /// Returns the [`Schema`] of this [`Key<T, F>`], if it is versioned.
+    pub const fn schema(&self) -> Option<&Schema> {
+        self.schema.as_ref()
+    }
+
+    /// Encodes the given value, including its schema version if this [`Key<T, F>`] is versioned.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value could not be encoded.
+    fn encode(&self, value: &T) -> Result<Vec<u8>, Error<F>> {
+        let Some(schema) = self.schema else {
+            return self.format.encode(value).map_err(Error::Encoding);
+        };
+
+        self.format
+            .encode(&Envelope { version: schema.version(), data: value })
+            .map_err(Error::Encoding)
+    }
+
+    /// Decodes the given bytes, migrating the value if it has an older schema version.
+    ///
+    /// Returns the value and whether it was migrated.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value could not be decoded or migrated.
+    fn decode(&self, bytes: &[u8]) -> Result<(T, bool), Error<F>> {
+        let Some(schema) = self.schema else {
+            return Ok((self.format.decode(bytes).map_err(Error::Decoding)?, false));
+        };
+
+        if let Ok(Envelope { version, data }) = self.format.decode::<Envelope<T>>(bytes) {
+            if version == schema.version() {
+                return Ok((data, false));
+            }
+        }
+
+        // Values without an envelope were stored before the type was versioned.
+        let (version, data) = match self.format.decode::<Envelope<serde_json::Value>>(bytes) {
+            Ok(Envelope { version, data }) => (version, data),
+            // Legacy values that do not need to be migrated are left as they are until they are
+            // next written, so that reading a value never rewrites it.
+            Err(_) if schema.version() == 0 => {
+                return Ok((self.format.decode(bytes).map_err(Error::Decoding)?, false));
+            }
+            Err(_) => (0, self.format.decode(bytes).map_err(Error::Decoding)?),
+        };
+        let data = schema.migrate(version, data).ok_or(Error::Version(version))?;
+
+        // The migrated value is re-encoded using this key's format rather than being converted
+        // directly, as formats may represent some types differently.
+        let bytes = self.format.encode(&data).map_err(Error::Encoding)?;
+
+        Ok((self.format.decode(&bytes).map_err(Error::Decoding)?, true))
+    }
+
+    /// Reads and decodes this [`Key<T, F>`]'s associated resource without writing back migrated
+    /// values.
     ///
     /// # Errors
     ///
     /// This function will return an error if the data could not be read or decoded.
+    fn read_value(&self) -> Result<T, Error<F>> {
+        self.decode(&self.backend.read(&self.path)?).map(|(value, _)| value)
+    }
+
+    /// Writes back the given migrated value if the resource has not changed and is not locked.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value could not be encoded or written.
+    fn write_migrated(&self, previous: &[u8], value: &T) -> Result<(), Error<F>> {
+        // Waiting here could deadlock if the caller is already holding this key's lock.
+        let Some(_lock) = self.backend.try_lock(&self.path)? else {
+            return Ok(());
+        };
+
+        if self.backend.read(&self.path)? != previous {
+            return Ok(());
+        }
+
+        // Migrating a value does not count as writing it, so its expiration time is kept.
+        self.write_value(value)
+    }
+
+    /// Reads this [`Key<T, F>`]'s associated resource.
+    ///
+    /// If this key is versioned and the stored value has an older schema version, the value is
+    /// migrated and written back.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data could not be read, decoded, or migrated.
     pub fn read(&self) -> Result<Value<T, F>, Error<F>> {
-        let bytes = std::fs::read(&(*self.path))?;
-        let value = self.format.decode(&bytes).map_err(Error::Decoding)?;
+        // Resources that were written within this process are assumed to only be changed by it.
+        let cache = self.cache.as_ref().filter(|c| {
+            crate::watch::is_watched(&self.path) || c.is_written(&self.backend, &self.path)
+        });
+        let generation = match cache.map(|c| c.get(&self.backend, &self.path)) {
+            Some(Ok(value)) => return Ok(Value { key: self, value }),
+            Some(Err(generation)) => Some(generation),
+            None => None,
+        };
+
+        let bytes = self.backend.read(&self.path)?;
+        let (value, migrated) = self.decode(&bytes)?;
+
+        if migrated {
+            // This is allowed to fail, as the value will just be migrated again on the next read.
+            self.write_migrated(&bytes, &value).ok();
+        }
+        if let (Some(cache), Some(generation)) = (cache, generation) {
+            cache.insert(&self.backend, &self.path, &value, generation);
+        }
 
         Ok(Value { key: self, value })
     }
@@ -167,17 +650,46 @@ where
 
     /// Writes the given value into this [`Key<T, F>`]'s associated resource.
     ///
+    /// The value is written atomically; if writing fails or is interrupted, the previously stored
+    /// resource is left untouched. If this key has a time-to-live, the resource's expiration time
+    /// is reset.
+    ///
     /// # Errors
     ///
     /// This function will return an error if the value could not be encoded or written.
     pub fn write(&self, value: &T) -> Result<(), Error<F>> {
-        let bytes = self.format.encode(value).map_err(Error::Encoding)?;
+        // The expiration time is written first, so an interrupted write never leaves behind a
+        // resource that does not expire.
+        if let Some(ttl) = self.ttl {
+            self.expire_in(ttl)?;
+        }
+
+        self.write_value(value)
+    }
+
+    /// Writes the given value into this [`Key<T, F>`]'s associated resource without changing its
+    /// expiration time.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value could not be encoded or written.
+    fn write_value(&self, value: &T) -> Result<(), Error<F>> {
+        let bytes = self.encode(value)?;
 
-        if let Some(dir) = self.path.parent() {
-            std::fs::create_dir_all(dir)?;
+        let _locks = crate::index::lock(&(*self.backend), &self.indexes, &[&self.path])?;
+        let _barrier = Barrier::shared(&(*self.backend))?;
+        let ledger = self.account(Some(bytes.len() as u64))?;
+
+        crate::watch::record(&self.backend, &self.path, Some(&bytes));
+
+        self.backend.write(&self.path, &bytes)?;
+        self.invalidate(true);
+
+        if let Some(ledger) = ledger {
+            ledger.save()?;
         }
 
-        std::fs::write(&(*self.path), bytes)?;
+        crate::index::update(&(*self.backend), &self.indexes, &self.path, Some(value))?;
 
         Ok(())
     }
@@ -188,7 +700,260 @@ where
     ///
     /// This function will return an error if the resource could not be removed.
     pub fn remove(&self) -> Result<(), Error<F>> {
-        std::fs::remove_file(&(*self.path)).map_err(Into::into)
+        let _locks = crate::index::lock(&(*self.backend), &self.indexes, &[&self.path])?;
+        let _barrier = Barrier::shared(&(*self.backend))?;
+        let ledger = self.account(None)?;
+
+        crate::watch::record(&self.backend, &self.path, None);
+
+        self.backend.remove(&self.path)?;
+        self.invalidate(false);
+
+        if let Some(ledger) = ledger {
+            ledger.save()?;
+        }
+
+        crate::index::update(&(*self.backend), &self.indexes, &self.path, None)?;
+
+        // The resource may not expire, so this is allowed to fail.
+        self.backend.remove(&crate::expire::expiry_path(&self.path)).ok();
+
+        Ok(())
+    }
+
+    /// Returns the expiration time of this [`Key<T, F>`]'s associated resource, or [`None`] if it
+    /// does not expire.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the expiration time could not be read.
+    pub fn expires(&self) -> Result<Option<SystemTime>, Error<F>> {
+        let path = crate::expire::expiry_path(&self.path);
+
+        crate::expire::read(&(*self.backend), &path).map_err(Into::into)
+    }
+
+    /// Sets this [`Key<T, F>`]'s associated resource to expire after the given duration, replacing
+    /// any previous expiration time.
+    ///
+    /// Expired resources are removed by [`sweep_expired`], and remain readable until then.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the expiration time could not be written.
+    pub fn expire_in(&self, ttl: Duration) -> Result<(), Error<F>> {
+        let Some(time) = SystemTime::now().checked_add(ttl) else {
+            return Err(std::io::Error::new(ErrorKind::InvalidInput, "invalid duration").into());
+        };
+        let path = crate::expire::expiry_path(&self.path);
+
+        let _barrier = Barrier::shared(&(*self.backend))?;
+
+        self.backend.write(&path, &crate::expire::encode(time)).map_err(Into::into)
+    }
+
+    /// Removes the expiration time of this [`Key<T, F>`]'s associated resource, if it expires.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the expiration time could not be removed.
+    pub fn persist(&self) -> Result<(), Error<F>> {
+        let _barrier = Barrier::shared(&(*self.backend))?;
+
+        match self.backend.remove(&crate::expire::expiry_path(&self.path)) {
+            Err(error) if error.kind() != ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes the given value into this [`Key<T, F>`]'s associated resource, which expires after
+    /// the given duration.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the expiration time could not be written, or if the
+    /// value could not be encoded or written.
+    pub fn write_expiring(&self, value: &T, ttl: Duration) -> Result<(), Error<F>> {
+        // The expiration time is written first, so an interrupted write never leaves behind a
+        // resource that does not expire. This overrides the key's own time-to-live.
+        self.expire_in(ttl)?;
+        self.write_value(value)
+    }
+
+    /// Acquires an exclusive [`Lock`] over this [`Key<T, F>`], blocking until it is available.
+    ///
+    /// Locks are advisory; they only exclude other holders of the same lock, such as concurrent
+    /// calls to [`update`](<Key::update>).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lock could not be acquired.
+    pub fn lock(&self) -> Result<Lock, Error<F>> {
+        self.backend.lock(&self.path).map_err(Into::into)
+    }
+
+    /// Reads, modifies, and writes this [`Key<T, F>`]'s associated resource while holding its
+    /// [`Lock`], returning the result of the given closure.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lock could not be acquired, or if the resource
+    /// could not be read, decoded, encoded, or written.
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, Error<F>> {
+        let _lock = self.lock()?;
+        let mut value = self.read_value()?;
+        let result = f(&mut value);
+
+        self.write(&value)?;
+
+        Ok(result)
+    }
+
+    /// Reads, modifies, and writes this [`Key<T, F>`]'s associated resource while holding its
+    /// [`Lock`], returning the result of the given closure.
+    ///
+    /// If the resource does not exist, the closure is given the default value. Unlike
+    /// [`read_or_default`](<Key::read_or_default>), a resource that exists but cannot be decoded
+    /// is never replaced.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lock could not be acquired, or if the resource
+    /// could not be read, decoded, encoded, or written.
+    pub fn update_or_default<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, Error<F>>
+    where
+        T: Default,
+    {
+        let _lock = self.lock()?;
+        let mut value = if self.exists()? { self.read_value()? } else { T::default() };
+        let result = f(&mut value);
+
+        self.write(&value)?;
+
+        Ok(result)
+    }
+
+    /// Reads, modifies, and writes all of the given keys' associated resources while holding each
+    /// key's [`Lock`], returning the result of the given closure.
+    ///
+    /// The closure receives the values in the same order as the given keys. Either every resource
+    /// is written, or none are; if any resource could not be written, those that were already
+    /// written are restored. Keys should be distinct, as only the last of any duplicate values is
+    /// kept, and must share the same storage backend.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the keys do not share a backend, if any lock could
+    /// not be acquired, or if any resource could not be read, decoded, encoded, or written.
+    pub fn update_all<R>(keys: &[&Self], f: impl FnOnce(&mut [T]) -> R) -> Result<R, Error<F>> {
+        let Some(backend) = keys.first().map(|k| &k.backend) else {
+            return Ok(f(&mut []));
+        };
+
+        if !keys.iter().all(|k| Arc::ptr_eq(&k.backend, backend)) {
+            let error = std::io::Error::new(ErrorKind::InvalidInput, "keys do not share a backend");
+
+            return Err(error.into());
+        }
+
+        // Locks are always acquired in the same order to prevent deadlocks between callers.
+        let mut paths = keys.iter().map(|k| k.path()).collect::<Box<[_]>>();
+
+        paths.sort_unstable();
+
+        let mut locks = Vec::with_capacity(paths.len());
+
+        for (index, path) in paths.iter().enumerate() {
+            if index == 0 || paths[index - 1] != *path {
+                locks.push(backend.lock(path)?);
+            }
+        }
+
+        let mut previous = Vec::with_capacity(keys.len());
+        let mut values = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            let bytes = backend.read(&key.path)?;
+
+            values.push(key.decode(&bytes)?.0);
+            previous.push(bytes);
+        }
+
+        let result = f(&mut values);
+        let mut encoded = Vec::with_capacity(keys.len());
+
+        for (key, value) in keys.iter().zip(&values) {
+            encoded.push(key.encode(value)?);
+        }
+
+        for key in keys {
+            if let Some(ttl) = key.ttl {
+                key.expire_in(ttl)?;
+            }
+        }
+
+        let files = keys.iter().zip(&encoded).zip(&previous);
+        let files = files.map(|((k, e), p)| (k.path(), &(**e), &(**p))).collect::<Box<[_]>>();
+
+        let indexes = keys.iter().map(|k| crate::index::lock(&(**backend), &k.indexes, &[&k.path]));
+        let indexes = indexes.collect::<std::io::Result<Box<[_]>>>()?;
+        let barrier = Barrier::shared(&(**backend))?;
+        let ledgers = Self::account_all(keys, &encoded)?;
+
+        for (key, bytes) in keys.iter().zip(&encoded) {
+            crate::watch::record(backend, &key.path, Some(bytes));
+        }
+
+        backend.write_all(&files)?;
+
+        for ledger in ledgers {
+            ledger.save()?;
+        }
+
+        for (key, value) in keys.iter().zip(&values) {
+            key.invalidate(true);
+
+            crate::index::update(&(**backend), &key.indexes, &key.path, Some(value))?;
+        }
+
+        drop(barrier);
+        drop(indexes);
+        drop(locks);
+
+        Ok(result)
+    }
+}
+
+impl<T, F> PartialEq for Key<T, F>
+where
+    T: Serialize + for<'de> Deserialize<'de>,
+    F: Format + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.backend, &other.backend)
+            && self.path == other.path
+            && self.format == other.format
+            && self.schema == other.schema
+    }
+}
+
+impl<T, F> Eq for Key<T, F>
+where
+    T: Serialize + for<'de> Deserialize<'de>,
+    F: Format + Eq,
+{
+}
+
+impl<T, F> Hash for Key<T, F>
+where
+    T: Serialize + for<'de> Deserialize<'de>,
+    F: Format + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Backends are not hashable, but keys within different backends may still share a hash.
+        self.path.hash(state);
+        self.format.hash(state);
+        self.schema.hash(state);
     }
 }
 
@@ -260,6 +1025,9 @@ where
     }
 }
 
+/// A list of stored resources of type `T` alongside their parsed arguments.
+pub type Listing<T> = Vec<(<T as Stored>::Arguments, Key<T, <T as Stored>::Format>)>;
+
 /// Provides a data storage key builder for the implementing type.
 pub trait Stored: Serialize + for<'de> Deserialize<'de> {
     /// The arguments provided when creating a new [`Key<T, F>`].
@@ -267,6 +1035,388 @@ pub trait Stored: Serialize + for<'de> Deserialize<'de> {
     /// The expected [`Format`] of this type.
     type Format: Format;
 
+    /// The location template of this type, relative to the storage root.
+    ///
+    /// Each `{}` placeholder within the template is filled by a single argument.
+    const LOCATION: &'static str;
+
     /// Creates a new [`Key<T, F>`] with the provided arguments.
     fn stored(arguments: Self::Arguments) -> Key<Self, Self::Format>;
+
+    /// Returns the [`Cache<T>`] shared by every key of this type, if this type is cached.
+    #[must_use]
+    fn cache() -> Option<Arc<Cache<Self>>>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    /// Returns the secondary indexes of this type.
+    #[must_use]
+    fn indexes() -> Arc<[Index<Self>]>
+    where
+        Self: Sized,
+    {
+        Arc::new([])
+    }
+
+    /// Parses the arguments that were used to fill the placeholders of this type's location.
+    ///
+    /// Returns [`None`] if the components are invalid or could not be parsed.
+    fn arguments(components: &[&str]) -> Option<Self::Arguments>;
+
+    /// Lists every stored resource of this type alongside its parsed arguments.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the storage backend could not be traversed.
+    fn list() -> Result<Listing<Self>, Error<Self::Format>>
+    where
+        Self::Arguments: Clone,
+    {
+        crate::location::list()
+    }
+
+    /// Lists every stored resource of this type whose first location argument is the given value,
+    /// alongside its parsed arguments.
+    ///
+    /// Unlike [`list`](<Stored::list>), only the resources within the given argument's directory
+    /// are traversed when the argument fills an entire path component.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the storage backend could not be traversed.
+    fn list_within(leading: impl Display) -> Result<Listing<Self>, Error<Self::Format>>
+    where
+        Self::Arguments: Clone,
+    {
+        crate::location::list_within(&[&Location::escape(&leading.to_string())])
+    }
+
+    /// Lists every stored resource of this type whose term within the named index matches the
+    /// given value, alongside its parsed arguments.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if this type does not have the named index, or if the
+    /// index could not be read.
+    fn query(index: &str, value: &impl Serialize) -> Result<Listing<Self>, Error<Self::Format>>
+    where
+        Self::Arguments: Clone,
+    {
+        crate::index::query(index, value)
+    }
+
+    /// Lists every stored resource of this type whose first location argument is the given value,
+    /// and whose term within the named index matches the given value.
+    ///
+    /// Unlike [`query`](<Stored::query>), only the index of the given argument is read when the
+    /// argument fills an entire path component.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if this type does not have the named index, or if the
+    /// index could not be read.
+    fn query_within(
+        leading: impl Display,
+        index: &str,
+        value: &impl Serialize,
+    ) -> Result<Listing<Self>, Error<Self::Format>>
+    where
+        Self::Arguments: Clone,
+    {
+        crate::index::query_within(&Location::escape(&leading.to_string()), index, value)
+    }
+
+    /// Rebuilds every secondary index of this type from its stored resources, returning the number
+    /// of indexed resources.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the storage backend could not be traversed, or if
+    /// any index could not be written.
+    fn reindex() -> Result<usize, Error<Self::Format>>
+    where
+        Self::Arguments: Clone,
+    {
+        crate::index::rebuild::<Self>()
+    }
+
+    /// Rebuilds every secondary index of this type only if any of them is missing or outdated,
+    /// returning the number of indexed resources if they were rebuilt.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the storage backend could not be traversed, or if
+    /// any index could not be written.
+    fn reindex_stale() -> Result<Option<usize>, Error<Self::Format>>
+    where
+        Self::Arguments: Clone,
+    {
+        if !crate::index::is_stale::<Self>()? {
+            return Ok(None);
+        }
+
+        crate::index::rebuild::<Self>().map(Some)
+    }
+
+    /// Records the size of every stored resource of this type within its namespace's usage
+    /// ledger, returning the number of accounted resources.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the storage backend could not be traversed, or if
+    /// any resource or ledger could not be read or written.
+    fn account() -> Result<usize, Error<Self::Format>>
+    where
+        Self::Arguments: Clone,
+    {
+        crate::quota::account::<Self>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::{Cache, Error, Format, Index, Key, Memory, Quota, Schema, ScopedBackend, Stored};
+
+    /// A [`Format`] that stores values as JSON, which is available without any features.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct Plain;
+
+    impl Format for Plain {
+        type EncodingError = serde_json::Error;
+        type DecodingError = serde_json::Error;
+
+        fn extension(&self) -> String {
+            "json".to_string()
+        }
+
+        fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, Self::EncodingError> {
+            serde_json::to_vec(value)
+        }
+
+        fn decode<T: for<'de> Deserialize<'de>>(
+            &self,
+            bytes: &[u8],
+        ) -> Result<T, Self::DecodingError> {
+            serde_json::from_slice(bytes)
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    struct Form {
+        status: u8,
+    }
+
+    impl Stored for Form {
+        type Arguments = (u64, u64);
+        type Format = Plain;
+
+        const LOCATION: &'static str = "form/{}/{}";
+
+        fn stored((guild, user): Self::Arguments) -> Key<Self, Self::Format> {
+            Key::from(format!("form/{guild}/{user}")).with_indexes(Self::indexes())
+        }
+
+        fn indexes() -> Arc<[Index<Self>]> {
+            Arc::new([Index::new(Self::LOCATION, "status", |f| serde_json::to_string(&f.status))])
+        }
+
+        fn arguments(components: &[&str]) -> Option<Self::Arguments> {
+            let [guild, user] = components else {
+                return None;
+            };
+
+            Some((guild.parse().ok()?, user.parse().ok()?))
+        }
+    }
+
+    #[test]
+    fn scoped_backend_across_threads() -> Result<(), Error<Plain>> {
+        let scope = ScopedBackend::new(Memory::new());
+        let _guard = scope.enter();
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                let _guard = scope.enter();
+
+                Key::<u8, Plain>::from("value").write(&1)
+            })
+            .join()
+            .unwrap_or_else(|p| std::panic::resume_unwind(p))
+        })?;
+
+        assert_eq!(Key::<u8, Plain>::from("value").read()?.get_owned(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn update_all_is_atomic() -> Result<(), Error<Plain>> {
+        let _guard = crate::install_local(Memory::new());
+        let (a, b) = (Key::<u8, Plain>::from("a"), Key::<u8, Plain>::from("b"));
+
+        a.write(&1)?;
+        b.write(&2)?;
+        Key::update_all(&[&a, &b], |v| v.iter_mut().for_each(|v| *v += 1))?;
+
+        assert_eq!((a.read()?.get_owned(), b.read()?.get_owned()), (2, 3));
+
+        // No value is written if any value could not be decoded.
+        crate::backend().write(b.path(), b"invalid")?;
+
+        assert!(Key::update_all(&[&a, &b], |v| v[0] += 1).is_err());
+        assert_eq!(a.read()?.get_owned(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn locks_exclude_other_threads() -> Result<(), Error<Plain>> {
+        let scope = ScopedBackend::new(Memory::new());
+        let _guard = scope.enter();
+        let key = Key::<u8, Plain>::from("value");
+        let is_locked = || {
+            std::thread::scope(|s| {
+                s.spawn(|| scope.backend().try_lock(key.path()).map(|l| l.is_none()))
+                    .join()
+                    .unwrap_or_else(|p| std::panic::resume_unwind(p))
+            })
+        };
+
+        let lock = key.lock()?;
+
+        assert!(is_locked()?);
+
+        drop(lock);
+
+        assert!(!is_locked()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn schema_migrates_and_writes_back() -> Result<(), Error<Plain>> {
+        let _guard = crate::install_local(Memory::new());
+        let migrations: &[fn(serde_json::Value) -> serde_json::Value] =
+            &[|v| serde_json::json!({ "status": v })];
+        let key = Key::<Form, Plain>::from("form").with_schema(Schema::new(1, migrations));
+
+        // Values stored before the type was versioned do not have an envelope.
+        crate::backend().write(key.path(), b"3")?;
+
+        assert_eq!(key.read()?.get_owned(), Form { status: 3 });
+        assert_eq!(crate::backend().read(key.path())?, br#"{"version":1,"data":{"status":3}}"#);
+
+        crate::backend().write(key.path(), br#"{"version":2,"data":{"status":3}}"#)?;
+
+        assert!(matches!(key.read(), Err(Error::Version(2))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn expired_values_are_swept() -> Result<(), Error<Plain>> {
+        let _guard = crate::install_local(Memory::new());
+        let (kept, expired) = (Key::<u8, Plain>::from("kept"), Key::<u8, Plain>::from("expired"));
+
+        kept.write_expiring(&1, Duration::from_secs(60 * 60))?;
+        expired.write_expiring(&2, Duration::ZERO)?;
+
+        assert_eq!(crate::sweep_expired()?.removed, 1);
+        assert!(kept.exists()?);
+        assert!(!expired.exists()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn written_values_are_cached() -> Result<(), Error<Plain>> {
+        let _guard = crate::install_local(Memory::new());
+        let cache = Arc::new(Cache::new(4));
+        let key = Key::<u8, Plain>::from("value").with_cache(Arc::clone(&cache));
+
+        // Values that were not written through a key may be changed elsewhere, so are not cached.
+        crate::backend().write(key.path(), b"1")?;
+
+        assert_eq!(key.read()?.get_owned(), 1);
+        assert_eq!((cache.stats().hits, cache.len()), (0, 0));
+
+        key.write(&2)?;
+
+        assert_eq!(key.read()?.get_owned(), 2);
+        assert_eq!(key.read()?.get_owned(), 2);
+        assert_eq!((cache.stats().hits, cache.len()), (1, 1));
+
+        cache.clear();
+
+        assert_eq!(key.read()?.get_owned(), 2);
+        assert!(cache.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn indexes_are_scoped_and_rebuilt() -> Result<(), Error<Plain>> {
+        let _guard = crate::install_local(Memory::new());
+        let backend = crate::backend();
+
+        for (guild, user, status) in [(1, 1, 0), (1, 2, 1), (2, 1, 0)] {
+            Form::stored((guild, user)).write(&Form { status })?;
+        }
+
+        let users =
+            |list: crate::Listing<Form>| list.into_iter().map(|(a, _)| a).collect::<Vec<_>>();
+
+        assert_eq!(users(Form::query("status", &0)?), [(1, 1), (2, 1)]);
+        assert_eq!(users(Form::query_within(1, "status", &0)?), [(1, 1)]);
+        assert!(backend.exists(Path::new("form/2/.status.index"))?);
+        assert_eq!(Form::reindex_stale()?, None);
+
+        Form::stored((2, 1)).write(&Form { status: 1 })?;
+
+        assert_eq!(users(Form::query_within(2, "status", &1)?), [(2, 1)]);
+
+        // Missing and outdated index files are rebuilt.
+        backend.remove(Path::new("form/2/.status.index"))?;
+        backend.write(Path::new("form/.status.index"), b"{}")?;
+
+        assert_eq!(Form::reindex_stale()?, Some(3));
+        assert!(!backend.exists(Path::new("form/.status.index"))?);
+        assert_eq!(users(Form::query("status", &1)?), [(1, 2), (2, 1)]);
+
+        Form::stored((1, 2)).remove()?;
+
+        assert_eq!(users(Form::query("status", &1)?), [(2, 1)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn quota_limits_namespace() -> Result<(), Error<Plain>> {
+        // The quota is only enforced for this backend, so other tests are not affected.
+        let scope = ScopedBackend::with_quota(Memory::new(), Quota { bytes: None, keys: Some(1) });
+        let _guard = scope.enter();
+        let key = |name: &str| Key::<u8, Plain>::from(name).with_namespace("1");
+
+        key("a").write(&1)?;
+        key("a").write(&2)?;
+
+        assert!(matches!(key("b").write(&1), Err(Error::Quota(..))));
+        assert!(!key("b").exists()?);
+
+        key("a").remove()?;
+        key("b").write(&1)?;
+
+        assert_eq!(crate::usage("1")?, crate::Usage { bytes: 1, keys: 1 });
+
+        Ok(())
+    }
 }
@@ -0,0 +1,452 @@
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+use crate::{Backend, Entry, Error, Listing, Stored};
+
+/// The hexadecimal digits used when escaping location arguments.
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Returns whether the given byte may be written unescaped within a location argument.
+const fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_')
+}
+
+/// A single piece of a [`Location`] segment.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+enum Piece {
+    /// A literal string.
+    Literal(Box<str>),
+    /// An argument placeholder.
+    Placeholder,
+}
+
+/// A single path component of a [`Location`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+struct Segment {
+    /// The segment's pieces.
+    pieces: Box<[Piece]>,
+}
+
+impl Segment {
+    /// Parses a new [`Segment`] from the given template path component.
+    fn new(template: &str) -> Self {
+        let mut pieces = vec![];
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(char) = chars.next() {
+            match char {
+                '{' if chars.peek() == Some(&'{') => literal.push(chars.next().unwrap_or(char)),
+                '}' if chars.peek() == Some(&'}') => literal.push(chars.next().unwrap_or(char)),
+                '{' => {
+                    // Skip any formatting specifiers, as they cannot be reliably reversed.
+                    chars.by_ref().take_while(|c| *c != '}').for_each(drop);
+
+                    if !literal.is_empty() {
+                        pieces.push(Piece::Literal(std::mem::take(&mut literal).into_boxed_str()));
+                    }
+
+                    pieces.push(Piece::Placeholder);
+                }
+                char => literal.push(char),
+            }
+        }
+
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal.into_boxed_str()));
+        }
+
+        Self { pieces: pieces.into_boxed_slice() }
+    }
+
+    /// Returns the literal value of this [`Segment`] if it does not contain any placeholders.
+    fn literal(&self) -> Option<&str> {
+        match &(*self.pieces) {
+            [Piece::Literal(literal)] => Some(literal),
+            _ => None,
+        }
+    }
+
+    /// Returns whether this [`Segment`] consists of a single placeholder.
+    fn is_placeholder(&self) -> bool {
+        matches!(&(*self.pieces), [Piece::Placeholder])
+    }
+
+    /// Matches the given path component against this [`Segment`], pushing any captured arguments
+    /// into the given list and returning whether the component matched.
+    fn capture(&self, mut component: &str, captures: &mut Vec<Box<str>>) -> bool {
+        let mut pieces = self.pieces.iter().peekable();
+
+        while let Some(piece) = pieces.next() {
+            match piece {
+                Piece::Literal(literal) => {
+                    let Some(rest) = component.strip_prefix(&(**literal)) else {
+                        return false;
+                    };
+
+                    component = rest;
+                }
+                Piece::Placeholder => {
+                    let end = match pieces.peek() {
+                        Some(Piece::Literal(literal)) => component.find(&(**literal)),
+                        Some(Piece::Placeholder) => Some(0),
+                        None => Some(component.len()),
+                    };
+                    let Some(end @ 1 ..) = end else {
+                        return false;
+                    };
+
+                    captures.push(component[.. end].into());
+                    component = &component[end ..];
+                }
+            }
+        }
+
+        component.is_empty()
+    }
+}
+
+/// A list of arguments captured from a path alongside the path itself.
+pub type Captured = (Box<[Box<str>]>, Box<Path>);
+
+/// A parsed storage location template, such as `"role/{}/{}"`.
+///
+/// Each `{}` placeholder represents a single argument that is captured when matching a path.
+/// Placeholders may not span multiple path components, and arguments should be
+/// [escaped](<Location::escape>) so that they cannot.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Location {
+    /// The location's path components.
+    segments: Box<[Segment]>,
+}
+
+impl Location {
+    /// Parses a new [`Location`] from the given template.
+    #[must_use]
+    pub fn new(template: &str) -> Self {
+        Self { segments: template.split('/').map(Segment::new).collect() }
+    }
+
+    /// Escapes the given argument so that it can be safely written into a single placeholder.
+    ///
+    /// Every byte other than an ASCII letter, digit, `-`, or `_` is percent-encoded, which
+    /// prevents arguments from containing path separators, parent directories, hidden file names,
+    /// or file extensions. Empty arguments are written as a single `%`. Placeholders that share a
+    /// path component should be separated by a literal that cannot appear unescaped, such as `~`.
+    #[must_use]
+    pub fn escape(argument: &str) -> Cow<'_, str> {
+        if argument.is_empty() {
+            return Cow::Borrowed("%");
+        }
+        if argument.bytes().all(self::is_unreserved) {
+            return Cow::Borrowed(argument);
+        }
+
+        let mut escaped = String::with_capacity(argument.len() * 3);
+
+        for byte in argument.bytes() {
+            if self::is_unreserved(byte) {
+                escaped.push(char::from(byte));
+            } else {
+                escaped.push('%');
+                escaped.push(char::from(HEX_DIGITS[usize::from(byte >> 4)]));
+                escaped.push(char::from(HEX_DIGITS[usize::from(byte & 0xF)]));
+            }
+        }
+
+        Cow::Owned(escaped)
+    }
+
+    /// Reverses the [escaping](<Location::escape>) of the given captured argument, returning
+    /// [`None`] if it is not validly escaped.
+    ///
+    /// Only the exact encoding produced by [`escape`](<Location::escape>) is accepted, so every
+    /// argument is stored at exactly one path. This rejects lowercase hexadecimal digits and
+    /// escaped bytes that would have been written unescaped.
+    #[must_use]
+    pub fn unescape(component: &str) -> Option<Cow<'_, str>> {
+        if component == "%" {
+            return Some(Cow::Borrowed(""));
+        }
+        if component.bytes().all(self::is_unreserved) {
+            return Some(Cow::Borrowed(component));
+        }
+
+        let mut bytes = Vec::with_capacity(component.len());
+        let mut iterator = component.bytes();
+
+        while let Some(byte) = iterator.next() {
+            if self::is_unreserved(byte) {
+                bytes.push(byte);
+
+                continue;
+            }
+            if byte != b'%' {
+                return None;
+            }
+
+            let (high, low) = (iterator.next()?, iterator.next()?);
+            let high = HEX_DIGITS.iter().position(|d| *d == high)?;
+            let low = HEX_DIGITS.iter().position(|d| *d == low)?;
+            let byte = u8::try_from((high << 4) | low).ok()?;
+
+            if self::is_unreserved(byte) {
+                return None;
+            }
+
+            bytes.push(byte);
+        }
+
+        String::from_utf8(bytes).ok().map(Cow::Owned)
+    }
+
+    /// Returns the number of argument placeholders within this [`Location`].
+    #[must_use]
+    pub fn placeholders(&self) -> usize {
+        let pieces = self.segments.iter().flat_map(|s| s.pieces.iter());
+
+        pieces.filter(|p| matches!(p, Piece::Placeholder)).count()
+    }
+
+    /// Returns the leading directories of this [`Location`] that do not contain any placeholders.
+    #[must_use]
+    pub fn prefix(&self) -> PathBuf {
+        let directories = &self.segments[.. self.segments.len().saturating_sub(1)];
+
+        directories.iter().map_while(Segment::literal).collect()
+    }
+
+    /// Returns the number of leading directories of this [`Location`] up to and including the
+    /// first directory that is filled by a single argument, such as `2` for `role/{}/{}`.
+    ///
+    /// Returns [`None`] if the first directory that contains a placeholder is not filled by a
+    /// single argument, or if there is no such directory.
+    #[must_use]
+    pub fn scope_depth(&self) -> Option<usize> {
+        let depth = self.segments.iter().take_while(|s| s.literal().is_some()).count();
+        let is_directory = depth + 1 < self.segments.len();
+
+        (is_directory && self.segments[depth].is_placeholder()).then_some(depth + 1)
+    }
+
+    /// Matches the given extension-less location against this [`Location`], returning the
+    /// captured arguments if it matches.
+    #[must_use]
+    pub fn capture(&self, location: &str) -> Option<Box<[Box<str>]>> {
+        let components = location.split('/').collect::<Box<[_]>>();
+
+        if components.len() != self.segments.len() {
+            return None;
+        }
+
+        let mut captures = vec![];
+        let mut pairs = self.segments.iter().zip(components.iter());
+
+        pairs.all(|(s, c)| s.capture(c, &mut captures)).then_some(captures.into_boxed_slice())
+    }
+
+    /// Walks the given backend, returning the captured arguments and relative path of every
+    /// resource that matches this [`Location`].
+    ///
+    /// File extensions are ignored while matching.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the backend could not be traversed.
+    pub fn walk(&self, backend: &dyn Backend) -> std::io::Result<Vec<Captured>> {
+        self.walk_within(backend, &[])
+    }
+
+    /// Walks the given backend, returning the captured arguments and relative path of every
+    /// resource that matches this [`Location`] and whose leading arguments are the given escaped
+    /// arguments.
+    ///
+    /// Directories named by a fixed argument are entered directly, so only the matching part of
+    /// the backend is traversed. File extensions are ignored while matching.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the backend could not be traversed.
+    pub fn walk_within(
+        &self,
+        backend: &dyn Backend,
+        leading: &[&str],
+    ) -> std::io::Result<Vec<Captured>> {
+        let mut found = vec![];
+        let mut queue = vec![(0, PathBuf::new(), vec![])];
+
+        while let Some((depth, dir, captures)) = queue.pop() {
+            let Some(segment) = self.segments.get(depth) else {
+                continue;
+            };
+            let last = depth + 1 == self.segments.len();
+
+            // Literal directories can be entered directly without listing their parent.
+            if let (false, Some(literal)) = (last, segment.literal()) {
+                queue.push((depth + 1, dir.join(literal), captures));
+
+                continue;
+            }
+            // As can directories that are named by a fixed argument.
+            if let (false, true, Some(argument)) =
+                (last, segment.is_placeholder(), leading.get(captures.len()))
+            {
+                let path = dir.join(crate::backend::from_location(argument)?);
+                let mut captures = captures;
+
+                captures.push((*argument).into());
+                queue.push((depth + 1, path, captures));
+
+                continue;
+            }
+
+            for Entry { path, is_dir } in backend.entries(&dir)? {
+                if is_dir == last {
+                    continue;
+                }
+
+                let name = if last { path.file_stem() } else { path.file_name() };
+                let Some(name) = name.and_then(|n| n.to_str()) else {
+                    continue;
+                };
+
+                let mut captures = captures.clone();
+
+                if !segment.capture(name, &mut captures) {
+                    continue;
+                }
+                if !captures.iter().zip(leading).all(|(a, b)| &(**a) == *b) {
+                    continue;
+                }
+
+                if last {
+                    found.push((captures.into_boxed_slice(), path));
+                } else {
+                    queue.push((depth + 1, path.into_path_buf(), captures));
+                }
+            }
+        }
+
+        Ok(found)
+    }
+}
+
+/// Lists every stored resource of the given [`Stored`] type alongside its parsed arguments.
+///
+/// Entries are sorted by their file path.
+///
+/// # Errors
+///
+/// This function will return an error if the storage backend could not be traversed.
+pub fn list<T>() -> Result<Listing<T>, Error<T::Format>>
+where
+    T: Stored,
+    T::Arguments: Clone,
+{
+    self::list_within(&[])
+}
+
+/// Lists every stored resource of the given [`Stored`] type whose leading arguments are the given
+/// escaped arguments, alongside its parsed arguments.
+///
+/// Entries are sorted by their file path.
+///
+/// # Errors
+///
+/// This function will return an error if the storage backend could not be traversed.
+pub fn list_within<T>(leading: &[&str]) -> Result<Listing<T>, Error<T::Format>>
+where
+    T: Stored,
+    T::Arguments: Clone,
+{
+    let location = Location::new(T::LOCATION);
+    let mut list = vec![];
+
+    for (captures, path) in location.walk_within(&(*crate::backend()), leading)? {
+        let components = captures.iter().map(|c| &(**c)).collect::<Box<[_]>>();
+        let Some(arguments) = T::arguments(&components) else {
+            continue;
+        };
+        let key = T::stored(arguments.clone());
+
+        // This ensures that the file's extension matches the expected format.
+        if key.path() == &(*path) {
+            list.push((arguments, key));
+        }
+    }
+
+    list.sort_unstable_by(|(_, a), (_, b)| a.path().cmp(b.path()));
+
+    Ok(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Location;
+
+    #[test]
+    fn escape_round_trip() {
+        for argument in ["", "plain", "with space", "a/b", "..", ".hidden", "x.json", "%", "ü~"] {
+            let escaped = Location::escape(argument);
+
+            assert!(escaped.bytes().all(|b| b.is_ascii_alphanumeric() || b"-_%".contains(&b)));
+            assert_eq!(Location::unescape(&escaped).as_deref(), Some(argument));
+        }
+    }
+
+    #[test]
+    fn unescape_rejects_non_canonical() {
+        for component in ["%2f", "%+1", "%41", "%2", "%%", "a%", "a.b", "%ZZ", "%FF"] {
+            assert_eq!(Location::unescape(component), None, "accepted '{component}'");
+        }
+    }
+
+    #[test]
+    fn capture_escaped_arguments() {
+        let location = Location::new("role/{}/{}");
+        let captures = location.capture("role/a%2Fb/%").unwrap_or_default();
+
+        assert_eq!(&(*captures), [Box::from("a%2Fb"), Box::from("%")]);
+        assert_eq!(Location::unescape(&captures[0]).as_deref(), Some("a/b"));
+        assert_eq!(location.placeholders(), 2);
+        assert_eq!(location.capture("role/a"), None);
+    }
+
+    #[test]
+    fn scope_depth_of_leading_directory() {
+        assert_eq!(Location::new("membership/{}/{}").scope_depth(), Some(2));
+        assert_eq!(Location::new("{}/data").scope_depth(), Some(1));
+        assert_eq!(Location::new("config/{}").scope_depth(), None);
+        assert_eq!(Location::new("form/{}-{}/data").scope_depth(), None);
+        assert_eq!(Location::new("static/data").scope_depth(), None);
+    }
+
+    #[test]
+    fn walk_within_leading_arguments() -> std::io::Result<()> {
+        use crate::Backend;
+
+        let backend = crate::backend::Memory::new();
+
+        for path in ["form/1/2.json", "form/1/3.json", "form/10/2.json", "form/2/1.json"] {
+            backend.write(path.as_ref(), b"{}")?;
+        }
+
+        let location = Location::new("form/{}/{}");
+        let captured = |leading: &[&str]| -> std::io::Result<Vec<Box<str>>> {
+            let found = location.walk_within(&backend, leading)?;
+
+            let mut found = found.into_iter().map(|(c, _)| c.join("/").into()).collect::<Vec<_>>();
+
+            found.sort_unstable();
+
+            Ok(found)
+        };
+
+        assert_eq!(captured(&[])?.len(), 4);
+        assert_eq!(captured(&["1"])?, [Box::from("1/2"), Box::from("1/3")]);
+        assert_eq!(captured(&["1", "3"])?, [Box::from("1/3")]);
+        assert!(captured(&["3"])?.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs::{File, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::Backend;
+
+/// The file extension used for lock files.
+pub const LOCK_EXTENSION: &str = "lock";
+/// The name of the lock file that guards the storage barrier across processes.
+pub const BARRIER_FILE: &str = ".snapshot.lock";
+
+/// A set of paths that are currently locked within this process.
+#[derive(Debug, Default)]
+pub struct LockSet {
+    /// The locked paths.
+    locked: Mutex<HashSet<Box<Path>>>,
+    /// Notified whenever a path is unlocked.
+    condvar: Condvar,
+}
+
+impl LockSet {
+    /// Acquires the locked path set, recovering it if a previous holder panicked.
+    fn acquire(&self) -> MutexGuard<'_, HashSet<Box<Path>>> {
+        // The set is never left in an inconsistent state, so poisoning can be safely ignored.
+        self.locked.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Returns the hidden lock file path for the given file path.
+///
+/// Lock files are named `.{file}.lock` and are left in place after being released, as they may be
+/// waited on by other processes; see [`sweep_expired`](<crate::sweep_expired>) for their removal.
+#[must_use]
+pub fn lock_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".{LOCK_EXTENSION}"));
+
+    path.with_file_name(name)
+}
+
+/// Returns the resource path of the given lock file path, or [`None`] if the path is not a lock
+/// file.
+#[must_use]
+pub fn resource_path(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let name = name.strip_prefix('.')?.strip_suffix(LOCK_EXTENSION)?.strip_suffix('.')?;
+
+    (!name.is_empty()).then(|| path.with_file_name(name))
+}
+
+/// Returns whether the given path is a lock file.
+#[must_use]
+pub fn is_lock_file(path: &Path) -> bool {
+    let hidden = path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.'));
+
+    hidden && path.extension().is_some_and(|e| e == LOCK_EXTENSION)
+}
+
+/// Opens the given lock file, creating it if necessary.
+///
+/// # Errors
+///
+/// This function will return an error if the lock file could not be created or opened.
+fn open_lock_file(path: &Path) -> std::io::Result<File> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    File::options().create(true).truncate(false).write(true).open(path)
+}
+
+/// Returns whether the given open lock file is still the file at the given path.
+///
+/// Unused lock files may be removed while another holder is waiting to lock them, in which case
+/// the waiting holder must retry with the new file.
+///
+/// # Errors
+///
+/// This function will return an error if either file's metadata could not be read.
+#[cfg(unix)]
+fn is_current(file: &File, path: &Path) -> std::io::Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    let open = file.metadata()?;
+    let current = match std::fs::metadata(path) {
+        Ok(current) => current,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(error) => return Err(error),
+    };
+
+    Ok(open.dev() == current.dev() && open.ino() == current.ino())
+}
+
+/// Returns whether the given open lock file is still the file at the given path.
+///
+/// Lock files are never removed on platforms other than Unix, so this always returns `true`.
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+const fn is_current(_: &File, _: &Path) -> std::io::Result<bool> {
+    Ok(true)
+}
+
+/// An exclusive lock over a single storage path.
+///
+/// The lock is held within this process and, if the path has an associated lock file, across
+/// processes through an advisory file lock. It is released when dropped.
+#[derive(Debug)]
+pub struct Lock {
+    /// The set that contains the locked path.
+    set: Arc<LockSet>,
+    /// The locked path.
+    path: Box<Path>,
+    /// The locked file and its path, if it was created.
+    file: Option<(File, Box<Path>)>,
+}
+
+impl Lock {
+    /// Acquires an exclusive lock over the given path within the given set, also locking the given
+    /// lock file if present, blocking until both are available.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lock file could not be created or locked.
+    pub fn acquire(set: &Arc<LockSet>, path: &Path, file: Option<&Path>) -> std::io::Result<Self> {
+        let mut guard = set.acquire();
+
+        while guard.contains(path) {
+            guard = set.condvar.wait(guard).unwrap_or_else(PoisonError::into_inner);
+        }
+
+        guard.insert(path.into());
+        drop(guard);
+
+        // From here on, dropping the lock will release the in-process lock.
+        let mut lock = Self { set: Arc::clone(set), path: path.into(), file: None };
+
+        while let Some(path) = file {
+            let file = self::open_lock_file(path)?;
+
+            file.lock()?;
+
+            if self::is_current(&file, path)? {
+                lock.file = Some((file, path.into()));
+
+                break;
+            }
+        }
+
+        Ok(lock)
+    }
+
+    /// Attempts to acquire an exclusive lock over the given path within the given set, also
+    /// locking the given lock file if present, without blocking.
+    ///
+    /// Returns [`None`] if the lock is currently held, including by the current thread.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lock file could not be created or locked.
+    pub fn try_acquire(
+        set: &Arc<LockSet>,
+        path: &Path,
+        file: Option<&Path>,
+    ) -> std::io::Result<Option<Self>> {
+        let mut guard = set.acquire();
+
+        if guard.contains(path) {
+            return Ok(None);
+        }
+
+        guard.insert(path.into());
+        drop(guard);
+
+        // From here on, dropping the lock will release the in-process lock.
+        let mut lock = Self { set: Arc::clone(set), path: path.into(), file: None };
+
+        while let Some(path) = file {
+            let file = self::open_lock_file(path)?;
+
+            match file.try_lock() {
+                Ok(()) if self::is_current(&file, path)? => {
+                    lock.file = Some((file, path.into()));
+
+                    break;
+                }
+                Ok(()) => {}
+                Err(TryLockError::WouldBlock) => return Ok(None),
+                Err(TryLockError::Error(error)) => return Err(error),
+            }
+        }
+
+        Ok(Some(lock))
+    }
+
+    /// Returns a reference to the locked path.
+    #[must_use]
+    pub const fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Removes the lock file of this [`Lock`] while it is still held, then releases the lock.
+    ///
+    /// Lock files are only removed on Unix, where holders that were waiting on the removed file
+    /// detect its removal and retry with a new file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lock file could not be removed.
+    pub fn remove_file(self) -> std::io::Result<()> {
+        match &self.file {
+            Some((_, path)) if cfg!(unix) => std::fs::remove_file(path),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        // Closing the file releases its advisory lock, but unlocking explicitly ensures that the
+        // lock is released before other threads are notified.
+        if let Some((file, _)) = self.file.take() {
+            file.unlock().ok();
+        }
+
+        self.set.acquire().remove(&(*self.path));
+        self.set.condvar.notify_all();
+    }
+}
+
+/// A hold over the storage barrier, which prevents resources from being changed while the whole
+/// backend is being read, such as while a snapshot is being captured.
+///
+/// The barrier is held within this process and, if the backend has a
+/// [`barrier_file`](<Backend::barrier_file>), across processes through an advisory file lock. It
+/// is released when dropped.
+#[derive(Debug)]
+pub struct Barrier {
+    /// The shared in-process barrier, if held.
+    _shared: Option<RwLockReadGuard<'static, ()>>,
+    /// The exclusive in-process barrier, if held.
+    _exclusive: Option<RwLockWriteGuard<'static, ()>>,
+    /// The locked barrier file, if the backend has one.
+    file: Option<File>,
+}
+
+impl Barrier {
+    /// Acquires a shared hold over the storage barrier of the given backend, blocking until it is
+    /// available.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the barrier file could not be created or locked.
+    pub fn shared(backend: &dyn Backend) -> std::io::Result<Self> {
+        let shared = crate::BARRIER.read().unwrap_or_else(PoisonError::into_inner);
+        // Each hold opens its own file, as advisory locks belong to an open file, not a thread.
+        let file = backend.barrier_file().map(|p| self::open_lock_file(&p)).transpose()?;
+
+        if let Some(file) = &file {
+            file.lock_shared()?;
+        }
+
+        Ok(Self { _shared: Some(shared), _exclusive: None, file })
+    }
+
+    /// Acquires an exclusive hold over the storage barrier of the given backend, blocking until
+    /// it is available.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the barrier file could not be created or locked.
+    pub fn exclusive(backend: &dyn Backend) -> std::io::Result<Self> {
+        let exclusive = crate::BARRIER.write().unwrap_or_else(PoisonError::into_inner);
+        let file = backend.barrier_file().map(|p| self::open_lock_file(&p)).transpose()?;
+
+        if let Some(file) = &file {
+            file.lock()?;
+        }
+
+        Ok(Self { _shared: None, _exclusive: Some(exclusive), file })
+    }
+}
+
+impl Drop for Barrier {
+    fn drop(&mut self) {
+        // The file is unlocked before the in-process barrier is released by dropping its guard.
+        if let Some(file) = self.file.take() {
+            file.unlock().ok();
+        }
+    }
+}
@@ -0,0 +1,477 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{PoisonError, RwLock};
+
+use crate::{Backend, Error, Lock, Stored};
+
+/// The directory that contains the usage ledger of every namespace.
+pub const QUOTA_DIR: &str = ".quota";
+
+/// The file extension used for usage ledgers.
+pub const USAGE_EXTENSION: &str = "usage";
+
+/// Stores the globally installed storage quota.
+static QUOTA: RwLock<Quota> = RwLock::new(Quota::UNLIMITED);
+
+/// The minimum number of superseded records that a usage ledger may contain before it is compacted.
+const COMPACT_THRESHOLD: usize = 64;
+
+/// The contents of a usage ledger, mapping the location of each resource to its size in bytes.
+type Entries = BTreeMap<Box<str>, u64>;
+
+/// A single record of a usage ledger, containing a resource's location and its size in bytes, or
+/// [`None`] if it was removed.
+type Record = (Box<str>, Option<u64>);
+
+/// The amount of storage used by a single namespace.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Usage {
+    /// The total size of every resource in bytes.
+    pub bytes: u64,
+    /// The number of resources.
+    pub keys: u64,
+}
+
+impl Display for Usage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Self { bytes, keys } = self;
+
+        write!(f, "{bytes} bytes in {keys} keys")
+    }
+}
+
+/// The maximum amount of storage that may be used by each namespace.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Quota {
+    /// The maximum total size of every resource in bytes, if limited.
+    pub bytes: Option<u64>,
+    /// The maximum number of resources, if limited.
+    pub keys: Option<u64>,
+}
+
+impl Quota {
+    /// A quota that does not limit storage.
+    pub const UNLIMITED: Self = Self { bytes: None, keys: None };
+
+    /// Returns whether a namespace's usage may change from the given previous usage to the given
+    /// usage.
+    ///
+    /// Changes that do not increase an exceeded limit are always allowed, so namespaces that were
+    /// filled before their quota was lowered may still shrink.
+    #[must_use]
+    pub fn allows(&self, previous: Usage, usage: Usage) -> bool {
+        let fits = |limit: Option<u64>, previous: u64, usage: u64| {
+            limit.is_none_or(|limit| usage <= limit || usage <= previous)
+        };
+
+        fits(self.bytes, previous.bytes, usage.bytes) && fits(self.keys, previous.keys, usage.keys)
+    }
+}
+
+/// Returns the globally installed storage [`Quota`], which is [unlimited](<Quota::UNLIMITED>) by
+/// default.
+#[must_use]
+pub fn quota() -> Quota {
+    *QUOTA.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Installs the given [`Quota`] as the global storage quota, enforced for every namespace.
+///
+/// Only keys that have been given a namespace through
+/// [`Key::with_namespace`](<crate::Key::with_namespace>) are limited. Backends that were installed
+/// with their own quota through [`ScopedBackend`](<crate::ScopedBackend::with_quota>) are not
+/// affected.
+pub fn install_quota(quota: Quota) {
+    *QUOTA.write().unwrap_or_else(PoisonError::into_inner) = quota;
+}
+
+/// Returns the path of the usage ledger of the given namespace, relative to the storage root.
+///
+/// # Errors
+///
+/// This function will return an error if the namespace is not a single valid path component.
+pub fn ledger_path(namespace: &str) -> std::io::Result<PathBuf> {
+    let mut components = Path::new(namespace).components();
+
+    let (Some(Component::Normal(_)), None) = (components.next(), components.next()) else {
+        return Err(std::io::Error::new(ErrorKind::InvalidInput, "invalid namespace"));
+    };
+
+    Ok(Path::new(QUOTA_DIR).join(format!("{namespace}.{USAGE_EXTENSION}")))
+}
+
+/// Returns whether the given path is a usage ledger.
+#[must_use]
+pub fn is_ledger_file(path: &Path) -> bool {
+    let within = path.components().next().is_some_and(|c| c.as_os_str() == QUOTA_DIR);
+
+    within && path.extension().is_some_and(|e| e == USAGE_EXTENSION)
+}
+
+/// The usage ledger of a single namespace, which is locked until dropped.
+///
+/// Ledgers are stored as a log of JSON records, one per line, and changes are appended onto the
+/// log rather than rewriting it. The log is compacted into a single record per resource once most
+/// of its records have been superseded.
+#[derive(Debug)]
+pub struct Ledger<'b> {
+    /// The ledger's storage backend.
+    backend: &'b dyn Backend,
+    /// The ledger's path.
+    path: PathBuf,
+    /// The ledger's entries.
+    entries: Entries,
+    /// The number of records within the stored log.
+    records: usize,
+    /// Whether the stored log must be rewritten, such as if its last record was incomplete.
+    rewrite: bool,
+    /// The namespace's usage when the ledger was opened.
+    previous: Usage,
+    /// The unsaved records.
+    changes: Vec<Record>,
+    /// The ledger's lock.
+    _lock: Lock,
+}
+
+impl<'b> Ledger<'b> {
+    /// Locks and reads the usage ledger of the given namespace, blocking until it is available.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the namespace is invalid, or if the ledger could not
+    /// be locked, read, or decoded.
+    pub fn open(backend: &'b dyn Backend, namespace: &str) -> std::io::Result<Self> {
+        let path = self::ledger_path(namespace)?;
+        let lock = backend.lock(&path)?;
+        let Log { entries, records, intact } = self::read(backend, &path)?;
+        let previous = self::usage_of(&entries);
+
+        Ok(Self {
+            backend,
+            path,
+            entries,
+            records,
+            rewrite: !intact,
+            previous,
+            changes: vec![],
+            _lock: lock,
+        })
+    }
+
+    /// Returns the namespace's current usage, including any unsaved changes.
+    #[must_use]
+    pub fn usage(&self) -> Usage {
+        self::usage_of(&self.entries)
+    }
+
+    /// Returns whether the unsaved changes of this [`Ledger`] are allowed by the given [`Quota`].
+    #[must_use]
+    pub fn allowed_by(&self, quota: &Quota) -> bool {
+        quota.allows(self.previous, self.usage())
+    }
+
+    /// Records that the resource at the given path now has the given size in bytes, or was
+    /// removed if no size is given.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the path is not a valid location.
+    pub fn set(&mut self, path: &Path, bytes: Option<u64>) -> std::io::Result<()> {
+        let location = crate::backend::location(path)?.into_boxed_str();
+
+        let previous = match bytes {
+            Some(bytes) => self.entries.insert(location.clone(), bytes),
+            None => self.entries.remove(&location),
+        };
+
+        if previous != bytes {
+            self.changes.push((location, bytes));
+        }
+
+        Ok(())
+    }
+
+    /// Appends any changes onto the ledger, compacting it if needed, or removes it if it is empty.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the ledger could not be written or removed.
+    pub fn save(self) -> std::io::Result<()> {
+        if self.changes.is_empty() && !self.rewrite {
+            return Ok(());
+        }
+        if self.entries.is_empty() {
+            return match self.backend.remove(&self.path) {
+                Err(error) if error.kind() != ErrorKind::NotFound => Err(error),
+                _ => Ok(()),
+            };
+        }
+
+        let records = self.records + self.changes.len();
+        let superseded = records.saturating_sub(self.entries.len());
+
+        if self.rewrite || superseded > self.entries.len().max(COMPACT_THRESHOLD) {
+            let records = self.entries.iter().map(|(l, b)| (l.clone(), Some(*b)));
+
+            return self.backend.write(&self.path, &self::encode(records)?);
+        }
+
+        self.backend.append(&self.path, &self::encode(self.changes)?)
+    }
+}
+
+/// Returns the total usage of the given ledger entries.
+fn usage_of(entries: &Entries) -> Usage {
+    Usage { bytes: entries.values().sum(), keys: entries.len() as u64 }
+}
+
+/// Encodes the given records as a usage ledger log.
+///
+/// # Errors
+///
+/// This function will return an error if any record could not be encoded.
+fn encode(records: impl IntoIterator<Item = Record>) -> std::io::Result<Vec<u8>> {
+    let mut bytes = vec![];
+
+    for record in records {
+        serde_json::to_writer(&mut bytes, &record)?;
+        bytes.push(b'\n');
+    }
+
+    Ok(bytes)
+}
+
+/// The decoded contents of a stored usage ledger.
+#[derive(Debug, Default)]
+struct Log {
+    /// The ledger's entries.
+    entries: Entries,
+    /// The number of records within the log.
+    records: usize,
+    /// Whether every record was complete.
+    intact: bool,
+}
+
+/// Reads the usage ledger at the given path, returning an empty ledger if it does not exist.
+///
+/// A trailing record that was only partially appended is ignored, and ledgers that were stored as
+/// a single JSON object are read as-is.
+///
+/// # Errors
+///
+/// This function will return an error if the ledger could not be read or is invalid.
+fn read(backend: &dyn Backend, path: &Path) -> std::io::Result<Log> {
+    let bytes = match backend.read(path) {
+        Ok(bytes) => bytes,
+        Err(error) if error.kind() == ErrorKind::NotFound => {
+            return Ok(Log { intact: true, ..Log::default() });
+        }
+        Err(error) => return Err(error),
+    };
+
+    // Ledgers were previously stored as a single object, and are rewritten as a log once changed.
+    if bytes.first() == Some(&b'{') {
+        let entries = serde_json::from_slice::<Entries>(&bytes)?;
+
+        return Ok(Log { records: entries.len(), entries, intact: false });
+    }
+
+    let end = bytes.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+    let (complete, partial) = bytes.split_at(end);
+    let mut log = Log { intact: partial.is_empty(), ..Log::default() };
+
+    for line in complete.split(|b| *b == b'\n').filter(|l| !l.is_empty()) {
+        let (location, size) = serde_json::from_slice::<Record>(line)?;
+
+        match size {
+            Some(size) => log.entries.insert(location, size),
+            None => log.entries.remove(&location),
+        };
+
+        log.records += 1;
+    }
+
+    Ok(log)
+}
+
+/// Returns the storage used by the given namespace within the installed storage
+/// [`backend`](<crate::backend>).
+///
+/// Usage is only tracked for values written through keys that have been given a namespace.
+///
+/// # Errors
+///
+/// This function will return an error if the namespace is invalid, or if its ledger could not be
+/// read.
+pub fn usage(namespace: &str) -> std::io::Result<Usage> {
+    let backend = crate::backend();
+
+    self::read(&(*backend), &self::ledger_path(namespace)?).map(|l| self::usage_of(&l.entries))
+}
+
+/// Recounts the storage used by the given namespace within the installed storage
+/// [`backend`](<crate::backend>), returning its corrected usage.
+///
+/// Resources that were modified or removed without using a key, such as by hand, are updated
+/// within the namespace's ledger.
+///
+/// # Errors
+///
+/// This function will return an error if the namespace is invalid, or if its ledger or any of its
+/// resources could not be read or written.
+pub fn recount(namespace: &str) -> std::io::Result<Usage> {
+    let backend = crate::backend();
+    let _barrier = crate::lock::Barrier::shared(&(*backend))?;
+    let mut ledger = Ledger::open(&(*backend), namespace)?;
+
+    for location in ledger.entries.clone().into_keys() {
+        let path = crate::backend::from_location(&location)?;
+        let bytes = match backend.read(&path) {
+            Ok(bytes) => Some(bytes.len() as u64),
+            Err(error) if error.kind() == ErrorKind::NotFound => None,
+            Err(error) => return Err(error),
+        };
+
+        ledger.set(&path, bytes)?;
+    }
+
+    let usage = ledger.usage();
+
+    ledger.save()?;
+
+    Ok(usage)
+}
+
+/// Records the size of every stored resource of the given [`Stored`] type within its namespace's
+/// usage ledger, returning the number of accounted resources.
+///
+/// This should be used to account for resources that were stored before their type was given a
+/// namespace. Resources without a namespace are skipped.
+///
+/// # Errors
+///
+/// This function will return an error if the storage backend could not be traversed, or if any
+/// resource or ledger could not be read or written.
+pub fn account<T>() -> Result<usize, Error<T::Format>>
+where
+    T: Stored,
+    T::Arguments: Clone,
+{
+    let backend = crate::backend();
+    let mut sizes = BTreeMap::<Box<str>, Vec<(Box<Path>, u64)>>::new();
+
+    for (_, key) in T::list()? {
+        let Some(namespace) = key.namespace() else {
+            continue;
+        };
+        let bytes = match backend.read(key.path()) {
+            Ok(bytes) => bytes.len() as u64,
+            // The resource may have been removed since the backend was traversed.
+            Err(error) if error.kind() == ErrorKind::NotFound => continue,
+            Err(error) => return Err(error.into()),
+        };
+
+        sizes.entry(namespace.into()).or_default().push((key.path().into(), bytes));
+    }
+
+    let _barrier = crate::lock::Barrier::shared(&(*backend))?;
+    let mut count = 0;
+
+    for (namespace, entries) in sizes {
+        let mut ledger = Ledger::open(&(*backend), &namespace)?;
+
+        for (path, bytes) in &entries {
+            ledger.set(path, Some(*bytes))?;
+        }
+
+        ledger.save()?;
+        count += entries.len();
+    }
+
+    Ok(count)
+}
+
+/// Removes the given resource paths from every usage ledger within the given backend.
+///
+/// The caller must hold the storage barrier.
+///
+/// # Errors
+///
+/// This function will return an error if any ledger could not be read or written.
+pub fn forget(backend: &dyn Backend, paths: &[Box<Path>]) -> std::io::Result<()> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    for entry in backend.entries(Path::new(QUOTA_DIR))? {
+        if entry.is_dir || !self::is_ledger_file(&entry.path) {
+            continue;
+        }
+
+        let Some(namespace) = entry.path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let mut ledger = Ledger::open(backend, namespace)?;
+
+        for path in paths {
+            ledger.set(path, None)?;
+        }
+
+        ledger.save()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{Ledger, Usage, COMPACT_THRESHOLD};
+    use crate::{Backend, Memory};
+
+    #[test]
+    fn ledger_appends_and_compacts() -> std::io::Result<()> {
+        let backend = Memory::new();
+        let path = super::ledger_path("1")?;
+        let records = || -> std::io::Result<usize> {
+            Ok(backend.read(&path)?.split(|b| *b == b'\n').count() - 1)
+        };
+
+        for size in 1 ..= 3 {
+            let mut ledger = Ledger::open(&backend, "1")?;
+
+            ledger.set(Path::new("a.json"), Some(size))?;
+            ledger.save()?;
+        }
+
+        assert_eq!(records()?, 3);
+        assert_eq!(super::read(&backend, &path)?.entries.get("a.json"), Some(&3));
+
+        for size in 0 ..= COMPACT_THRESHOLD as u64 {
+            let mut ledger = Ledger::open(&backend, "1")?;
+
+            ledger.set(Path::new("b.json"), Some(size))?;
+            ledger.save()?;
+        }
+
+        assert!(records()? <= COMPACT_THRESHOLD);
+
+        // An interrupted append leaves a partial record, which is dropped by the next save.
+        backend.append(&path, b"[\"c.json\",")?;
+
+        let mut ledger = Ledger::open(&backend, "1")?;
+
+        assert_eq!(ledger.usage(), Usage { bytes: 3 + COMPACT_THRESHOLD as u64, keys: 2 });
+
+        ledger.set(Path::new("a.json"), None)?;
+        ledger.save()?;
+
+        assert_eq!(records()?, 1);
+        assert!(super::read(&backend, &path)?.intact);
+
+        Ok(())
+    }
+}
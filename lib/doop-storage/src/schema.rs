@@ -0,0 +1,73 @@
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+
+/// A function that upgrades a stored payload from one schema version to the next.
+pub type Migration = fn(serde_json::Value) -> serde_json::Value;
+
+/// Describes the current schema version of a stored type, and how to upgrade older payloads.
+///
+/// Payloads of a versioned type are stored alongside their version. Payloads that were stored
+/// without a version are treated as version `0`, and are only stored alongside their version once
+/// they are next written or migrated.
+#[derive(Clone, Copy, Debug)]
+pub struct Schema {
+    /// The current schema version.
+    version: u32,
+    /// The schema's migrations, where the migration at index `n` upgrades version `n` to `n + 1`.
+    migrations: &'static [Migration],
+}
+
+impl Schema {
+    /// Creates a new [`Schema`].
+    ///
+    /// The migration at index `n` must upgrade a payload from version `n` to version `n + 1`, and
+    /// there should be exactly one migration for each version below the current version.
+    #[must_use]
+    pub const fn new(version: u32, migrations: &'static [Migration]) -> Self {
+        Self { version, migrations }
+    }
+
+    /// Returns the current version of this [`Schema`].
+    #[must_use]
+    pub const fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Upgrades the given payload from the given version to the current version.
+    ///
+    /// Returns [`None`] if the payload's version is newer than the current version, or if a
+    /// required migration is missing.
+    #[must_use]
+    pub fn migrate(&self, version: u32, value: serde_json::Value) -> Option<serde_json::Value> {
+        let start = usize::try_from(version).ok()?;
+        let end = usize::try_from(self.version).ok()?;
+
+        Some(self.migrations.get(start .. end)?.iter().fold(value, |v, f| f(v)))
+    }
+}
+
+impl PartialEq for Schema {
+    fn eq(&self, other: &Self) -> bool {
+        // Migrations are function pointers, which cannot be reliably compared.
+        self.version == other.version
+    }
+}
+
+impl Eq for Schema {}
+
+impl Hash for Schema {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.version.hash(state);
+    }
+}
+
+/// A stored payload alongside its schema version.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Envelope<T> {
+    /// The payload's schema version.
+    pub version: u32,
+    /// The payload.
+    pub data: T,
+}
@@ -0,0 +1,352 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+use sha2::{Digest, Sha256};
+
+use crate::lock::Barrier;
+use crate::{Backend, Format, MsgPack};
+
+/// The bytes that every snapshot archive begins with.
+const MAGIC: [u8; 8] = *b"DOOPSNAP";
+/// The current snapshot archive version.
+const VERSION: u8 = 1;
+/// Marks a snapshot archive as compressed.
+const COMPRESSED: u8 = 1 << 0;
+/// Marks a snapshot archive as encrypted.
+const ENCRYPTED: u8 = 1 << 1;
+
+/// An error that may occur while capturing, saving, loading, or restoring a [`Snapshot`].
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotError {
+    /// An IO error.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// A [`Format`] error while encoding or decoding an archive.
+    #[error("{0}")]
+    Format(String),
+    /// The archive is not a snapshot archive.
+    #[error("invalid snapshot archive")]
+    Invalid,
+    /// The archive's version is newer than the supported version.
+    #[error("unsupported snapshot version {0}")]
+    Version(u8),
+    /// The archive requires a cargo feature that is not enabled.
+    #[error("snapshot archive requires the `{0}` feature")]
+    Feature(&'static str),
+    /// An entry's contents do not match its checksum.
+    #[error("checksum mismatch for '{0}'")]
+    Checksum(String),
+    /// The backend already contains entries.
+    #[error("the storage backend is not empty")]
+    NotEmpty,
+}
+
+/// Describes a single entry within a [`Snapshot`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// The entry's location, relative to the storage root.
+    pub location: String,
+    /// The entry's size in bytes.
+    pub size: u64,
+    /// The entry's SHA-256 checksum, encoded as lowercase hexadecimal.
+    pub checksum: String,
+}
+
+/// Describes the contents of a [`Snapshot`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// The time that the snapshot was captured, in seconds since the Unix epoch.
+    pub created: u64,
+    /// The snapshot's entries, sorted by location.
+    pub entries: Vec<ManifestEntry>,
+}
+
+/// Configures how a [`Snapshot`] archive is saved.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct ArchiveOptions {
+    /// Whether the archive is compressed. This requires the `compress` feature.
+    pub compress: bool,
+    /// Whether the archive is encrypted. This requires the `encrypt` feature.
+    pub encrypt: bool,
+}
+
+/// A consistent copy of every entry within a storage backend.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// The snapshot's manifest.
+    manifest: Manifest,
+    /// The contents of each entry, in the same order as the manifest.
+    contents: Vec<ByteBuf>,
+}
+
+/// Returns the SHA-256 checksum of the given bytes, encoded as lowercase hexadecimal.
+fn checksum(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Encodes the given snapshot using the given format.
+///
+/// # Errors
+///
+/// This function will return an error if the snapshot could not be encoded.
+fn encode<F: Format>(format: &F, snapshot: &Snapshot) -> Result<Vec<u8>, SnapshotError> {
+    format.encode(snapshot).map_err(|e| SnapshotError::Format(e.to_string()))
+}
+
+/// Decodes a snapshot using the given format.
+///
+/// # Errors
+///
+/// This function will return an error if the snapshot could not be decoded.
+fn decode<F: Format>(format: &F, bytes: &[u8]) -> Result<Snapshot, SnapshotError> {
+    format.decode(bytes).map_err(|e| SnapshotError::Format(e.to_string()))
+}
+
+/// Returns the error for an archive that requires a disabled feature.
+const fn missing_feature(compress: bool) -> SnapshotError {
+    if compress && cfg!(not(feature = "compress")) {
+        SnapshotError::Feature("compress")
+    } else {
+        SnapshotError::Feature("encrypt")
+    }
+}
+
+impl Snapshot {
+    /// Captures a new [`Snapshot`] of every entry within the given backend for which the given
+    /// filter returns `true`.
+    ///
+    /// Keys cannot be written while the snapshot is being captured, including by other processes
+    /// that share the backend, and every backend writes its entries atomically, so the snapshot
+    /// never contains partial writes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the backend could not be traversed or read.
+    pub fn capture(
+        backend: &dyn Backend,
+        mut filter: impl FnMut(&Path) -> bool,
+    ) -> std::io::Result<Self> {
+        let barrier = Barrier::exclusive(backend)?;
+        let mut files = vec![];
+
+        for path in crate::backend::files(backend)? {
+            if filter(&path) {
+                files.push((crate::backend::location(&path)?, backend.read(&path)?));
+            }
+        }
+
+        drop(barrier);
+        files.sort_unstable();
+
+        let created = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let entries = files.iter().map(|(location, bytes)| ManifestEntry {
+            location: location.clone(),
+            size: bytes.len() as u64,
+            checksum: self::checksum(bytes),
+        });
+        let manifest = Manifest { created, entries: entries.collect() };
+        let contents = files.into_iter().map(|(_, bytes)| ByteBuf::from(bytes)).collect();
+
+        Ok(Self { manifest, contents })
+    }
+
+    /// Returns a reference to the manifest of this [`Snapshot`].
+    #[must_use]
+    pub const fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    /// Verifies that every entry of this [`Snapshot`] matches its checksum.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any entry does not match its checksum.
+    pub fn verify(&self) -> Result<(), SnapshotError> {
+        if self.manifest.entries.len() != self.contents.len() {
+            return Err(SnapshotError::Invalid);
+        }
+
+        for (entry, bytes) in self.manifest.entries.iter().zip(&self.contents) {
+            if entry.size != bytes.len() as u64 || entry.checksum != self::checksum(bytes) {
+                return Err(SnapshotError::Checksum(entry.location.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Restores every entry of this [`Snapshot`] into the given backend, which must not contain
+    /// any entries for which the given filter returns `true`.
+    ///
+    /// The filter should match the one that the snapshot was captured with. Either every entry is
+    /// restored, or none are.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the snapshot could not be verified, if the backend
+    /// is not empty, or if any entry could not be written.
+    pub fn restore(
+        &self,
+        backend: &dyn Backend,
+        mut filter: impl FnMut(&Path) -> bool,
+    ) -> Result<(), SnapshotError> {
+        self.verify()?;
+
+        let _barrier = Barrier::exclusive(backend)?;
+
+        if crate::backend::files(backend)?.iter().any(|p| filter(p)) {
+            return Err(SnapshotError::NotEmpty);
+        }
+
+        let paths =
+            self.manifest.entries.iter().map(|e| crate::backend::from_location(&e.location));
+        let paths = paths.collect::<std::io::Result<Box<[_]>>>()?;
+        let files = paths.iter().zip(&self.contents).map(|(p, b)| (&(**p), &(***b), &[][..]));
+
+        backend.write_all(&files.collect::<Box<[_]>>()).map_err(Into::into)
+    }
+
+    /// Encodes this [`Snapshot`] into an archive using the given options.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the archive requires a disabled feature, or if the
+    /// snapshot could not be encoded.
+    pub fn to_archive(&self, options: ArchiveOptions) -> Result<Vec<u8>, SnapshotError> {
+        let body = match (options.compress, options.encrypt) {
+            (false, false) => self::encode(&MsgPack, self)?,
+            #[cfg(feature = "compress")]
+            (true, false) => self::encode(&crate::Compress::<MsgPack, 6>::default(), self)?,
+            #[cfg(feature = "encrypt")]
+            (false, true) => self::encode(&crate::Encrypt::<MsgPack>::default(), self)?,
+            #[cfg(all(feature = "compress", feature = "encrypt"))]
+            (true, true) => {
+                self::encode(&crate::Encrypt::<crate::Compress<MsgPack, 6>>::default(), self)?
+            }
+            #[allow(unreachable_patterns)]
+            _ => return Err(self::missing_feature(options.compress)),
+        };
+
+        let flags =
+            (u8::from(options.compress) * COMPRESSED) | (u8::from(options.encrypt) * ENCRYPTED);
+        let mut archive = Vec::with_capacity(MAGIC.len() + 2 + body.len());
+
+        archive.extend_from_slice(&MAGIC);
+        archive.push(VERSION);
+        archive.push(flags);
+        archive.extend_from_slice(&body);
+
+        Ok(archive)
+    }
+
+    /// Decodes a [`Snapshot`] from the given archive.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the archive is invalid, if it requires a disabled
+    /// feature, or if it could not be decoded.
+    pub fn from_archive(archive: &[u8]) -> Result<Self, SnapshotError> {
+        let Some(archive) = archive.strip_prefix(&MAGIC) else {
+            return Err(SnapshotError::Invalid);
+        };
+        let [version, flags, body @ ..] = archive else {
+            return Err(SnapshotError::Invalid);
+        };
+
+        if *version > VERSION {
+            return Err(SnapshotError::Version(*version));
+        }
+
+        let snapshot = match (flags & COMPRESSED != 0, flags & ENCRYPTED != 0) {
+            (false, false) => self::decode(&MsgPack, body)?,
+            #[cfg(feature = "compress")]
+            (true, false) => self::decode(&crate::Compress::<MsgPack, 6>::default(), body)?,
+            #[cfg(feature = "encrypt")]
+            (false, true) => self::decode(&crate::Encrypt::<MsgPack>::default(), body)?,
+            #[cfg(all(feature = "compress", feature = "encrypt"))]
+            (true, true) => {
+                self::decode(&crate::Encrypt::<crate::Compress<MsgPack, 6>>::default(), body)?
+            }
+            #[allow(unreachable_patterns)]
+            _ => return Err(self::missing_feature(flags & COMPRESSED != 0)),
+        };
+
+        Ok(snapshot)
+    }
+
+    /// Saves this [`Snapshot`] as an archive at the given path using the given options.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the archive could not be encoded or written.
+    pub fn save(
+        &self,
+        path: impl AsRef<Path>,
+        options: ArchiveOptions,
+    ) -> Result<(), SnapshotError> {
+        crate::atomic::write(path.as_ref(), &self.to_archive(options)?).map_err(Into::into)
+    }
+
+    /// Loads a [`Snapshot`] from the archive at the given path.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the archive could not be read or decoded.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SnapshotError> {
+        Self::from_archive(&std::fs::read(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{ArchiveOptions, Snapshot, SnapshotError};
+    use crate::{Backend, Memory};
+
+    #[test]
+    fn round_trip() -> Result<(), SnapshotError> {
+        let source = Memory::new();
+
+        source.write(Path::new("role/1/2.json"), b"{}")?;
+        source.write(Path::new("role/1/.2.json.expires"), b"0")?;
+        source.write(Path::new("lang/en-US.toml"), b"")?;
+
+        let is_data = |p: &Path| !p.starts_with("lang");
+        let snapshot = Snapshot::capture(&source, is_data)?;
+        let archive = snapshot.to_archive(ArchiveOptions::default())?;
+        let snapshot = Snapshot::from_archive(&archive)?;
+
+        assert_eq!(snapshot.manifest().entries.len(), 2);
+
+        // The filtered entries are not part of the snapshot, so they don't prevent its restoration.
+        let target = Memory::new();
+
+        target.write(Path::new("lang/en-US.toml"), b"")?;
+        snapshot.restore(&target, is_data)?;
+
+        assert_eq!(target.read(Path::new("role/1/2.json"))?, b"{}");
+        assert_eq!(target.read(Path::new("role/1/.2.json.expires"))?, b"0");
+        assert!(matches!(snapshot.restore(&target, is_data), Err(SnapshotError::NotEmpty)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_corrupted_archive() -> Result<(), SnapshotError> {
+        let source = Memory::new();
+
+        source.write(Path::new("role/1/2.json"), b"{}")?;
+
+        let mut snapshot = Snapshot::capture(&source, |_| true)?;
+
+        snapshot.contents[0] = b"[]".to_vec().into();
+
+        assert!(matches!(snapshot.verify(), Err(SnapshotError::Checksum(_))));
+        assert!(matches!(Snapshot::from_archive(b"DOOPSNAP"), Err(SnapshotError::Invalid)));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+use crate::lock::{Lock, LockSet};
+use crate::{Backend, Entry};
+
+/// The name of the metadata entry that marks a completed import.
+const IMPORTED: &str = "imported";
+
+/// Converts the given `SQLite` error into an IO error.
+fn io_error(error: rusqlite::Error) -> std::io::Error {
+    std::io::Error::other(error)
+}
+
+/// A [`Backend`] that stores resources within a single `SQLite` database.
+///
+/// Each resource is stored as a single row, keyed by its location string. Values are stored
+/// exactly as they would be on disk, so any [`Format`](<crate::Format>) can be used. Locks are only
+/// held within the current process.
+#[derive(Debug)]
+pub struct Sqlite {
+    /// The database connection.
+    connection: Mutex<Connection>,
+    /// The set of currently locked paths.
+    locks: Arc<LockSet>,
+}
+
+impl Sqlite {
+    /// Opens the `SQLite` database at the given path, creating it if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the database could not be opened or initialized.
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        if let Some(dir) = path.as_ref().parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        Self::new(Connection::open(path).map_err(self::io_error)?)
+    }
+
+    /// Opens a new `SQLite` database that only exists in memory.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the database could not be opened or initialized.
+    pub fn open_in_memory() -> std::io::Result<Self> {
+        Self::new(Connection::open_in_memory().map_err(self::io_error)?)
+    }
+
+    /// Creates a new [`Sqlite`] backend from the given connection, creating its table if needed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the database could not be initialized.
+    fn new(connection: Connection) -> std::io::Result<Self> {
+        connection
+            .execute_batch(
+                "PRAGMA journal_mode = WAL;
+                PRAGMA synchronous = NORMAL;
+                CREATE TABLE IF NOT EXISTS entries (
+                    location TEXT PRIMARY KEY NOT NULL,
+                    data BLOB NOT NULL
+                ) WITHOUT ROWID;
+                CREATE TABLE IF NOT EXISTS metadata (
+                    name TEXT PRIMARY KEY NOT NULL,
+                    value TEXT NOT NULL
+                ) WITHOUT ROWID;",
+            )
+            .map_err(self::io_error)?;
+
+        Ok(Self { connection: Mutex::new(connection), locks: Arc::default() })
+    }
+
+    /// Acquires the database connection, recovering it if a previous holder panicked.
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        // Every write happens within a statement or transaction, so the database is never left in
+        // an inconsistent state.
+        self.connection.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Runs the given closure within a transaction, committing it if the closure succeeds.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the closure fails or the transaction could not be
+    /// committed.
+    fn transact(
+        &self,
+        f: impl FnOnce(&Transaction<'_>) -> std::io::Result<()>,
+    ) -> std::io::Result<()> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(self::io_error)?;
+
+        f(&transaction)?;
+        transaction.commit().map_err(self::io_error)?;
+        drop(connection);
+
+        Ok(())
+    }
+
+    /// Returns whether a previous [`import`](<Sqlite::import>) into this database has completed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the database could not be queried.
+    pub fn is_imported(&self) -> std::io::Result<bool> {
+        self.connection()
+            .prepare_cached("SELECT 1 FROM metadata WHERE name = ?1")
+            .and_then(|mut s| s.exists([IMPORTED]))
+            .map_err(self::io_error)
+    }
+
+    /// Imports every resource within the given storage directory that matches the given filter,
+    /// returning the paths of every imported resource relative to the directory, or [`None`] if a
+    /// previous import has already completed.
+    ///
+    /// This is intended as a one-shot migration from a [`FileSystem`](<crate::FileSystem>)
+    /// backend. Temporary and lock files are always skipped, existing entries are replaced, and
+    /// the directory is left untouched. Either every resource is imported and the import is marked
+    /// as completed, or nothing is, so a failed import is retried by the next call.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the directory could not be traversed, a file could
+    /// not be read, or the resources could not be written.
+    pub fn import(
+        &self,
+        dir: &Path,
+        mut filter: impl FnMut(&Path) -> bool,
+    ) -> std::io::Result<Option<Box<[Box<Path>]>>> {
+        if self.is_imported()? {
+            return Ok(None);
+        }
+
+        let mut files = vec![];
+        let mut queue = vec![PathBuf::new()];
+
+        while let Some(relative) = queue.pop() {
+            let entries = match std::fs::read_dir(dir.join(&relative)) {
+                Ok(entries) => entries,
+                Err(error) if error.kind() == ErrorKind::NotFound => continue,
+                Err(error) => return Err(error),
+            };
+
+            for entry in entries {
+                let entry = entry?;
+                let path = relative.join(entry.file_name());
+
+                if entry.file_type()?.is_dir() {
+                    queue.push(path);
+
+                    continue;
+                }
+
+                let hidden = entry.file_name().to_string_lossy().starts_with('.');
+
+                if hidden || crate::atomic::is_temporary(&path) || !filter(&path) {
+                    continue;
+                }
+
+                files.push((path.into_boxed_path(), std::fs::read(entry.path())?));
+            }
+        }
+
+        files.sort_unstable();
+
+        self.transact(|transaction| {
+            for (path, bytes) in &files {
+                let location = crate::backend::location(path)?;
+
+                transaction
+                    .prepare_cached(
+                        "INSERT OR REPLACE INTO entries (location, data) VALUES (?1, ?2)",
+                    )
+                    .and_then(|mut s| s.execute(params![location, bytes]))
+                    .map_err(self::io_error)?;
+            }
+
+            // A plain insert ensures that two concurrent imports cannot both complete.
+            transaction
+                .execute("INSERT INTO metadata (name, value) VALUES (?1, ?2)", [IMPORTED, "1"])
+                .map(drop)
+                .map_err(self::io_error)
+        })?;
+
+        Ok(Some(files.into_iter().map(|(path, _)| path).collect()))
+    }
+}
+
+impl Backend for Sqlite {
+    fn exists(&self, path: &Path) -> std::io::Result<bool> {
+        let location = crate::backend::location(path)?;
+
+        self.connection()
+            .prepare_cached("SELECT 1 FROM entries WHERE location = ?1")
+            .and_then(|mut s| s.exists([location]))
+            .map_err(self::io_error)
+    }
+
+    fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        let location = crate::backend::location(path)?;
+        let data = self
+            .connection()
+            .prepare_cached("SELECT data FROM entries WHERE location = ?1")
+            .and_then(|mut s| s.query_row([location], |r| r.get(0)).optional())
+            .map_err(self::io_error)?;
+
+        data.ok_or_else(|| ErrorKind::NotFound.into())
+    }
+
+    fn write(&self, path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+        let location = crate::backend::location(path)?;
+
+        self.connection()
+            .prepare_cached("INSERT OR REPLACE INTO entries (location, data) VALUES (?1, ?2)")
+            .and_then(|mut s| s.execute(params![location, bytes]))
+            .map(drop)
+            .map_err(self::io_error)
+    }
+
+    fn write_all(&self, files: &[(&Path, &[u8], &[u8])]) -> std::io::Result<()> {
+        self.transact(|transaction| {
+            for (path, bytes, _) in files {
+                let location = crate::backend::location(path)?;
+
+                transaction
+                    .prepare_cached(
+                        "INSERT OR REPLACE INTO entries (location, data) VALUES (?1, ?2)",
+                    )
+                    .and_then(|mut s| s.execute(params![location, bytes]))
+                    .map_err(self::io_error)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn remove(&self, path: &Path) -> std::io::Result<()> {
+        let location = crate::backend::location(path)?;
+        let removed = self
+            .connection()
+            .prepare_cached("DELETE FROM entries WHERE location = ?1")
+            .and_then(|mut s| s.execute([location]))
+            .map_err(self::io_error)?;
+
+        if removed == 0 {
+            Err(ErrorKind::NotFound.into())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn entries(&self, dir: &Path) -> std::io::Result<Vec<Entry>> {
+        let location = crate::backend::location(dir)?;
+        // Every location within the directory starts with `{dir}/`, and `0` directly follows `/`.
+        let (start, end) = if location.is_empty() {
+            (String::new(), String::from(char::MAX))
+        } else {
+            (format!("{location}/"), format!("{location}0"))
+        };
+
+        let rows = self
+            .connection()
+            .prepare_cached("SELECT location FROM entries WHERE location >= ?1 AND location < ?2")
+            .and_then(|mut s| {
+                s.query_map([&start, &end], |r| r.get(0))?.collect::<Result<Vec<String>, _>>()
+            })
+            .map_err(self::io_error)?;
+        let mut entries = BTreeMap::new();
+
+        for row in rows.iter().map(String::as_str) {
+            let rest = &row[start.len() ..];
+            let (name, is_dir) =
+                rest.split_once('/').map_or((rest, false), |(name, _)| (name, true));
+
+            *entries.entry(dir.join(name)).or_insert(is_dir) |= is_dir;
+        }
+
+        Ok(entries.into_iter().map(|(p, is_dir)| Entry { path: p.into(), is_dir }).collect())
+    }
+
+    fn lock(&self, path: &Path) -> std::io::Result<Lock> {
+        Lock::acquire(&self.locks, path, None)
+    }
+
+    fn try_lock(&self, path: &Path) -> std::io::Result<Option<Lock>> {
+        Lock::try_acquire(&self.locks, path, None)
+    }
+}
@@ -0,0 +1,228 @@
+use std::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Format, Key, Value};
+
+/// Runs the given closure on the blocking thread pool of the current runtime.
+///
+/// # Panics
+///
+/// Panics if called outside of a Tokio runtime, or if the closure panics.
+///
+/// # Errors
+///
+/// This function will return an error if the closure returns an error, or if the task was
+/// cancelled.
+async fn blocking<F, R>(
+    f: impl FnOnce() -> Result<R, Error<F>> + Send + 'static,
+) -> Result<R, Error<F>>
+where
+    F: Format + 'static,
+    R: Send + 'static,
+    Error<F>: Send,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(error) if error.is_panic() => std::panic::resume_unwind(error.into_panic()),
+        Err(error) => Err(std::io::Error::other(error).into()),
+    }
+}
+
+impl<T, F> Key<T, F>
+where
+    T: Serialize + for<'de> Deserialize<'de> + Send + Sync + 'static,
+    F: Format + Clone + Send + Sync + 'static,
+    Error<F>: Send,
+{
+    /// Returns an owned copy of this [`Key<T, F>`] that can be moved onto another thread.
+    fn detached(&self) -> Self {
+        let (path, backend, format) =
+            (self.path.clone(), self.backend.clone(), self.format.clone());
+        let (schema, cache, indexes) = (self.schema, self.cache.clone(), self.indexes.clone());
+        let (namespace, ttl) = (self.namespace.clone(), self.ttl);
+
+        Self { path, backend, format, schema, cache, indexes, namespace, ttl, _marker: PhantomData }
+    }
+
+    /// Returns whether this [`Key<T, F>`] exists within the storage system without blocking the
+    /// current task.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the path could not be verified.
+    pub async fn exists_async(&self) -> Result<bool, Error<F>> {
+        let key = self.detached();
+
+        self::blocking(move || key.exists()).await
+    }
+
+    /// Reads this [`Key<T, F>`]'s associated resource without blocking the current task.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data could not be read or decoded.
+    pub async fn read_async(&self) -> Result<Value<'_, T, F>, Error<F>> {
+        let key = self.detached();
+        let value = self::blocking(move || key.read().map(Value::get_owned)).await?;
+
+        Ok(Value { key: self, value })
+    }
+
+    /// Writes the given value into this [`Key<T, F>`]'s associated resource without blocking the
+    /// current task.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value could not be encoded or written.
+    pub async fn write_async(&self, value: &T) -> Result<(), Error<F>>
+    where
+        T: Clone,
+    {
+        let key = self.detached();
+        let value = value.clone();
+
+        self::blocking(move || key.write(&value)).await
+    }
+
+    /// Removes the resource associated with this [`Key<T, F>`] without blocking the current task.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the resource could not be removed.
+    pub async fn remove_async(&self) -> Result<(), Error<F>> {
+        let key = self.detached();
+
+        self::blocking(move || key.remove()).await
+    }
+
+    /// Calls the given closure with this [`Key<T, F>`] while holding its [`Lock`](<crate::Lock>),
+    /// without blocking the current task.
+    ///
+    /// This should be used instead of [`lock`](<Key::lock>) within async code, as acquiring a lock
+    /// blocks the current thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lock could not be acquired, or if the closure
+    /// returns an error.
+    pub async fn locked_async<R>(
+        &self,
+        f: impl FnOnce(&Self) -> Result<R, Error<F>> + Send + 'static,
+    ) -> Result<R, Error<F>>
+    where
+        R: Send + 'static,
+    {
+        let key = self.detached();
+
+        self::blocking(move || {
+            let _lock = key.lock()?;
+
+            f(&key)
+        })
+        .await
+    }
+
+    /// Reads, modifies, and writes this [`Key<T, F>`]'s associated resource while holding its
+    /// [`Lock`](<crate::Lock>), without blocking the current task.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lock could not be acquired, or if the resource
+    /// could not be read, decoded, encoded, or written.
+    pub async fn update_async<R>(
+        &self,
+        f: impl FnOnce(&mut T) -> R + Send + 'static,
+    ) -> Result<R, Error<F>>
+    where
+        R: Send + 'static,
+    {
+        let key = self.detached();
+
+        self::blocking(move || key.update(f)).await
+    }
+
+    /// Reads, modifies, and writes this [`Key<T, F>`]'s associated resource while holding its
+    /// [`Lock`](<crate::Lock>), without blocking the current task.
+    ///
+    /// If the resource does not exist, the closure is given the default value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lock could not be acquired, or if the resource
+    /// could not be read, decoded, encoded, or written.
+    pub async fn update_or_default_async<R>(
+        &self,
+        f: impl FnOnce(&mut T) -> R + Send + 'static,
+    ) -> Result<R, Error<F>>
+    where
+        T: Default,
+        R: Send + 'static,
+    {
+        let key = self.detached();
+
+        self::blocking(move || key.update_or_default(f)).await
+    }
+}
+
+impl<T, F> Value<'_, T, F>
+where
+    T: Serialize + for<'de> Deserialize<'de> + Clone + Send + Sync + 'static,
+    F: Format + Clone + Send + Sync + 'static,
+    Error<F>: Send,
+{
+    /// Writes this [`Value<T, F>`] into its associated resource without blocking the current task.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value could not be encoded or written.
+    pub async fn write_async(&self) -> Result<(), Error<F>> {
+        self.key.write_async(&self.value).await
+    }
+
+    /// Removes the resource associated with this [`Value<T, F>`] without blocking the current
+    /// task.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the resource could not be removed.
+    pub async fn remove_async(&self) -> Result<(), Error<F>> {
+        self.key.remove_async().await
+    }
+}
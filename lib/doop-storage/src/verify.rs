@@ -0,0 +1,440 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::io::ErrorKind;
+use std::path::Path;
+
+use serde::de::IgnoredAny;
+
+use crate::{Backend, Error, Format, Key, Location, Stored, Value};
+
+/// The result of checking a single resource against a registered location.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+enum Check {
+    /// The resource does not belong to the location.
+    Unmatched,
+    /// The resource belongs to the location, but does not use its format's extension.
+    UnknownFormat,
+    /// The resource belongs to the location, but could not be decoded.
+    Undecodable(Box<str>),
+    /// The resource belongs to the location and was successfully decoded.
+    Valid,
+}
+
+/// A function that checks the resource at the given path, given its captured arguments.
+type CheckFn = fn(&[&str], &Path, &[u8]) -> Check;
+/// A function that reads the resource with the given captured arguments as JSON, returning
+/// [`None`] if the arguments are invalid.
+type DumpFn = fn(&[&str], &str) -> Option<std::io::Result<serde_json::Value>>;
+/// A function that writes the given JSON into the resource with the given captured arguments,
+/// returning [`None`] if the arguments are invalid.
+type LoadFn = fn(&[&str], &str, &serde_json::Value) -> Option<std::io::Result<()>>;
+
+/// A function that re-encrypts every outdated resource at a location, returning the number of
+/// re-encrypted resources.
+type RotateFn = fn() -> std::io::Result<usize>;
+
+/// Converts the given storage error into an IO error.
+fn into_io<F: Format>(error: Error<F>) -> std::io::Error {
+    match error {
+        Error::Io(error) => error,
+        error => std::io::Error::new(ErrorKind::InvalidData, error.to_string()),
+    }
+}
+
+/// Checks the given resource against the location of the [`Stored`] type `T`.
+fn check_stored<T: Stored>(captures: &[&str], path: &Path, bytes: &[u8]) -> Check {
+    let Some(arguments) = T::arguments(captures) else {
+        return Check::Unmatched;
+    };
+    let key = T::stored(arguments);
+
+    if key.path().with_extension("") != path.with_extension("") {
+        return Check::Unmatched;
+    }
+    if key.path() != path {
+        return Check::UnknownFormat;
+    }
+
+    match key.decode(bytes) {
+        Ok(_) => Check::Valid,
+        Err(error) => Check::Undecodable(error.to_string().into_boxed_str()),
+    }
+}
+
+/// Reads the resource of the [`Stored`] type `T` with the given arguments as JSON.
+fn dump_stored<T: Stored>(
+    captures: &[&str],
+    _: &str,
+) -> Option<std::io::Result<serde_json::Value>> {
+    let key = T::stored(T::arguments(captures)?);
+    // The value is decoded directly, as reading it through the key could write it back.
+    let bytes = crate::backend().read(key.path()).map_err(Error::Io);
+    let value = bytes.and_then(|b| key.decode(&b)).map_err(self::into_io);
+
+    Some(value.and_then(|(v, _)| serde_json::to_value(v).map_err(Into::into)))
+}
+
+/// Writes the given JSON into the resource of the [`Stored`] type `T` with the given arguments.
+fn load_stored<T: Stored>(
+    captures: &[&str],
+    _: &str,
+    value: &serde_json::Value,
+) -> Option<std::io::Result<()>> {
+    let key = T::stored(T::arguments(captures)?);
+    let value = T::deserialize(value).map_err(std::io::Error::from);
+
+    Some(value.and_then(|v| key.write(&v).map_err(self::into_io)))
+}
+
+/// Re-encrypts every outdated resource of the [`Stored`] type `T`.
+#[cfg(feature = "encrypt")]
+fn rotate_stored<T, F>() -> std::io::Result<usize>
+where
+    T: Stored<Format = crate::Encrypt<F>>,
+    T::Arguments: Clone,
+    F: Format,
+{
+    crate::encrypt::rotate_encrypted::<T, F>().map_err(self::into_io)
+}
+
+/// Checks the given resource against a location that stores values of any type using `F`.
+fn check_any<F: Format + Default>(_: &[&str], path: &Path, bytes: &[u8]) -> Check {
+    let format = F::default();
+
+    if path.extension().and_then(|e| e.to_str()) != Some(&(*format.extension())) {
+        return Check::UnknownFormat;
+    }
+
+    match format.decode::<IgnoredAny>(bytes) {
+        Ok(_) => Check::Valid,
+        Err(error) => Check::Undecodable(error.to_string().into_boxed_str()),
+    }
+}
+
+/// Reads the resource at the given location, which stores values of any type using `F`, as JSON.
+#[allow(clippy::unnecessary_wraps)] // must match `DumpFn`.
+fn dump_any<F: Format + Default>(
+    _: &[&str],
+    location: &str,
+) -> Option<std::io::Result<serde_json::Value>> {
+    let key = Key::<serde_json::Value, F>::new_default(location);
+
+    Some(key.read().map(Value::get_owned).map_err(self::into_io))
+}
+
+/// Writes the given JSON into the resource at the given location, which stores values of any type
+/// using `F`.
+#[allow(clippy::unnecessary_wraps)] // must match `LoadFn`.
+fn load_any<F: Format + Default>(
+    _: &[&str],
+    location: &str,
+    value: &serde_json::Value,
+) -> Option<std::io::Result<()>> {
+    let key = Key::<serde_json::Value, F>::new_default(location);
+
+    Some(key.write(value).map_err(self::into_io))
+}
+
+/// A problem found within a single stored resource.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum Issue {
+    /// The resource does not belong to any registered location.
+    Orphaned,
+    /// The resource belongs to a registered location, but does not use its expected format.
+    UnknownFormat,
+    /// The resource could not be decoded using its expected format.
+    Undecodable(Box<str>),
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Orphaned => write!(f, "orphaned resource"),
+            Self::UnknownFormat => write!(f, "unknown format"),
+            Self::Undecodable(error) => write!(f, "undecodable resource: {error}"),
+        }
+    }
+}
+
+/// The result of verifying every resource within a storage backend.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Report {
+    /// The number of resources that were checked.
+    pub checked: usize,
+    /// The path and problem of every resource that failed verification, sorted by path.
+    pub issues: Vec<(Box<Path>, Issue)>,
+}
+
+impl Report {
+    /// Returns whether every checked resource passed verification.
+    #[must_use]
+    pub const fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Moves every resource that failed verification out of the installed storage [`backend`]
+    /// and into the given directory, returning the paths of all moved resources.
+    ///
+    /// Resources keep their relative paths within the quarantine directory, and are removed
+    /// from the backend only after they have been written. The directory should be outside of the
+    /// storage root, otherwise quarantined resources will be reported again.
+    ///
+    /// [`backend`]: <crate::backend>
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a resource could not be read, written, or removed.
+    pub fn quarantine(&self, dir: impl AsRef<Path>) -> std::io::Result<Box<[Box<Path>]>> {
+        let backend = crate::backend();
+        let mut moved = Vec::with_capacity(self.issues.len());
+
+        for (path, _) in &self.issues {
+            let _lock = backend.lock(path)?;
+            let bytes = backend.read(path)?;
+
+            crate::atomic::write(&dir.as_ref().join(path), &bytes)?;
+
+            let _barrier = crate::lock::Barrier::shared(&(*backend))?;
+
+            backend.remove(path)?;
+            moved.push(path.clone());
+        }
+
+        Ok(moved.into_boxed_slice())
+    }
+}
+
+/// A single location within a [`Registry`].
+#[derive(Clone)]
+struct Entry {
+    /// The location's name.
+    name: &'static str,
+    /// The location's template.
+    location: Location,
+    /// Checks a resource at the location.
+    check: CheckFn,
+    /// Reads a resource at the location as JSON.
+    dump: DumpFn,
+    /// Writes JSON into a resource at the location.
+    load: LoadFn,
+    /// Re-encrypts the outdated resources at the location, if they are encrypted.
+    rotate: Option<RotateFn>,
+}
+
+/// A list of every known storage location, used to verify, inspect, and edit the resources of a
+/// storage backend.
+#[derive(Clone, Default)]
+pub struct Registry {
+    /// The registered locations.
+    entries: Vec<Entry>,
+}
+
+impl Registry {
+    /// Creates a new, empty [`Registry`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { entries: vec![] }
+    }
+
+    /// Returns this [`Registry`] with the location of the given [`Stored`] type.
+    #[must_use]
+    pub fn with<T: Stored>(mut self) -> Self {
+        self.entries.push(Entry {
+            name: std::any::type_name::<T>(),
+            location: Location::new(T::LOCATION),
+            check: self::check_stored::<T>,
+            dump: self::dump_stored::<T>,
+            load: self::load_stored::<T>,
+            rotate: None,
+        });
+
+        self
+    }
+
+    /// Returns this [`Registry`] with the location of the given encrypted [`Stored`] type, whose
+    /// resources are re-encrypted by [`rotate`](<Registry::rotate>).
+    #[cfg(feature = "encrypt")]
+    #[must_use]
+    pub fn with_encrypted<T, F>(mut self) -> Self
+    where
+        T: Stored<Format = crate::Encrypt<F>>,
+        T::Arguments: Clone,
+        F: Format,
+    {
+        self = self.with::<T>();
+
+        if let Some(entry) = self.entries.last_mut() {
+            entry.rotate = Some(self::rotate_stored::<T, F>);
+        }
+
+        self
+    }
+
+    /// Returns this [`Registry`] with the given location, which stores values of any type using
+    /// the format `F`.
+    ///
+    /// Values are only checked to be decodable, so `F` must be self-describing.
+    #[must_use]
+    pub fn with_location<F: Format + Default>(mut self, template: &'static str) -> Self {
+        self.entries.push(Entry {
+            name: template,
+            location: Location::new(template),
+            check: self::check_any::<F>,
+            dump: self::dump_any::<F>,
+            load: self::load_any::<F>,
+            rotate: None,
+        });
+
+        self
+    }
+
+    /// Verifies every resource within the installed storage [`backend`] for which the given
+    /// filter returns `true`.
+    ///
+    /// Each resource is matched against every registered location and decoded using that
+    /// location's format. Resources that do not match any location, that use an unexpected
+    /// format, or that could not be decoded are reported.
+    ///
+    /// [`backend`]: <crate::backend>
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the backend could not be traversed or read.
+    pub fn verify(&self, mut filter: impl FnMut(&Path) -> bool) -> std::io::Result<Report> {
+        let backend = crate::backend();
+        let mut report = Report::default();
+
+        for path in crate::backend::files(&(*backend))? {
+            // Expiry, index, and ledger files describe other resources rather than being resources
+            // themselves.
+            let is_sidecar = crate::expire::is_expiry_file(&path)
+                || crate::index::is_index_file(&path)
+                || crate::quota::is_ledger_file(&path);
+
+            if is_sidecar || !filter(&path) {
+                continue;
+            }
+
+            report.checked += 1;
+
+            if let Some(issue) = self.check(&(*backend), &path)? {
+                report.issues.push((path, issue));
+            }
+        }
+
+        report.issues.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+        Ok(report)
+    }
+
+    /// Calls the given function with the captured arguments of every registered location that
+    /// matches the given location, returning the first result.
+    ///
+    /// The location may optionally include its file extension.
+    fn find<R>(
+        &self,
+        location: &str,
+        mut f: impl FnMut(&Entry, &[&str], &str) -> Option<R>,
+    ) -> Option<R> {
+        let stripped = Path::new(location).with_extension("");
+        let stripped = stripped.to_str().filter(|s| *s != location);
+
+        for location in std::iter::once(location).chain(stripped) {
+            for entry in &self.entries {
+                let Some(captures) = entry.location.capture(location) else {
+                    continue;
+                };
+                let captures = captures.iter().map(|c| &(**c)).collect::<Box<[_]>>();
+
+                if let Some(result) = f(entry, &captures, location) {
+                    return Some(result);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Reads the resource at the given location using its registered type, returning its value as
+    /// JSON.
+    ///
+    /// The location is relative to the storage root, such as `role/1/2`, and may optionally include
+    /// its file extension.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the location is not registered, or if the resource
+    /// could not be read or decoded.
+    pub fn dump(&self, location: &str) -> std::io::Result<serde_json::Value> {
+        self.find(location, |entry, captures, location| (entry.dump)(captures, location))
+            .unwrap_or_else(|| Err(self::unknown_location(location)))
+    }
+
+    /// Writes the given JSON value into the resource at the given location, encoding it using the
+    /// location's registered type and format.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the location is not registered, if the value is not
+    /// valid for the location's type, or if the resource could not be encoded or written.
+    pub fn load(&self, location: &str, value: &serde_json::Value) -> std::io::Result<()> {
+        self.find(location, |entry, captures, location| (entry.load)(captures, location, value))
+            .unwrap_or_else(|| Err(self::unknown_location(location)))
+    }
+
+    /// Re-encrypts every resource of each registered encrypted type that was not encrypted using
+    /// the active key of the installed keyring, returning the number of re-encrypted resources.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any resource could not be listed, read, or written.
+    pub fn rotate(&self) -> std::io::Result<usize> {
+        self.entries
+            .iter()
+            .filter_map(|e| e.rotate)
+            .try_fold(0, |count, rotate| Ok(count + rotate()?))
+    }
+
+    /// Checks the resource at the given path, returning its problem if it has one.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the resource could not be read.
+    fn check(&self, backend: &dyn Backend, path: &Path) -> std::io::Result<Option<Issue>> {
+        let location = crate::backend::location(&path.with_extension(""))?;
+        let mut issue = Issue::Orphaned;
+        let mut bytes = None;
+
+        for Entry { location: template, check, .. } in &self.entries {
+            let Some(captures) = template.capture(&location) else {
+                continue;
+            };
+            let captures = captures.iter().map(|c| &(**c)).collect::<Box<[_]>>();
+            let bytes = match &bytes {
+                Some(bytes) => bytes,
+                None => bytes.insert(backend.read(path)?),
+            };
+
+            // A resource is valid if any location accepts it, as locations may overlap.
+            match check(&captures, path, bytes) {
+                Check::UnknownFormat if issue == Issue::Orphaned => issue = Issue::UnknownFormat,
+                Check::Unmatched | Check::UnknownFormat => {}
+                Check::Undecodable(error) => issue = Issue::Undecodable(error),
+                Check::Valid => return Ok(None),
+            }
+        }
+
+        Ok(Some(issue))
+    }
+}
+
+/// Returns the error for a location that does not match any registered location.
+fn unknown_location(location: &str) -> std::io::Error {
+    std::io::Error::new(ErrorKind::NotFound, format!("unknown location '{location}'"))
+}
+
+impl Debug for Registry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Checking functions are function pointers, which are not meaningful to print.
+        f.debug_list().entries(self.entries.iter().map(|e| e.name)).finish()
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::{Backend, Error, Key, Location, Stored};
+
+/// Identifies a single resource by its backend's identifier and its path.
+type ResourceKey = (u64, Box<Path>);
+
+/// The locations of every live [`Watcher<T>`], which may contain duplicates.
+static WATCHED: Mutex<Vec<Location>> = Mutex::new(Vec::new());
+
+/// The digest of every watched resource as it was last written within this process, or [`None`]
+/// if it was last removed.
+static WRITTEN: Mutex<BTreeMap<ResourceKey, Option<u64>>> = Mutex::new(BTreeMap::new());
+
+/// Acquires the given list, recovering it if a previous holder panicked.
+fn acquire<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // Every operation leaves the lists in a consistent state, so poisoning can be safely ignored.
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns the key of the resource at the given path within the given backend, or [`None`] if the
+/// backend was not installed.
+fn resource_key(backend: &Arc<dyn Backend>, path: &Path) -> Option<ResourceKey> {
+    backend.id().map(|id| (id, path.into()))
+}
+
+/// Returns whether the resource at the given path is currently being watched by any
+/// [`Watcher<T>`].
+pub fn is_watched(path: &Path) -> bool {
+    let watched = self::acquire(&WATCHED);
+
+    if watched.is_empty() {
+        return false;
+    }
+
+    let Ok(location) = crate::backend::location(&path.with_extension("")) else {
+        return false;
+    };
+
+    watched.iter().any(|l| l.capture(&location).is_some())
+}
+
+/// Returns the digest of the given bytes.
+fn digest(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Records that the resource at the given path was written with the given bytes, or removed if
+/// no bytes are given, within this process.
+///
+/// Changes are only recorded for resources that are currently being watched, and are not reported
+/// by any [`Watcher<T>`].
+pub fn record(backend: &Arc<dyn Backend>, path: &Path, bytes: Option<&[u8]>) {
+    let Some(key) = self::resource_key(backend, path) else {
+        return;
+    };
+
+    if self::is_watched(path) {
+        self::acquire(&WRITTEN).insert(key, bytes.map(self::digest));
+    }
+}
+
+/// A kind of change made to a watched resource.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Change {
+    /// The resource was created.
+    Created,
+    /// The resource's contents were modified.
+    Modified,
+    /// The resource was removed.
+    Removed,
+}
+
+/// A change made to a watched resource of type `T`.
+#[derive(Clone, Debug)]
+pub struct Event<T: Stored> {
+    /// The kind of change.
+    pub change: Change,
+    /// The arguments that were used to fill the placeholders of the resource's location.
+    pub arguments: T::Arguments,
+    /// The resource's key.
+    pub key: Key<T, T::Format>,
+}
+
+/// The arguments and digest of every stored resource of type `T`, keyed by path.
+type Resources<T> = BTreeMap<Box<Path>, (<T as Stored>::Arguments, u64)>;
+
+/// Watches every stored resource of type `T` for changes that were made outside of this process,
+/// such as when a file is edited by hand.
+///
+/// Watchers compare the contents of each resource whenever they are [polled](<Watcher::poll>), so
+/// they are best suited to small, rarely written types such as configurations. Changes made
+/// through keys within this process are never reported, and changed values are invalidated within
+/// the type's [cache](<Stored::cache>).
+pub struct Watcher<T: Stored> {
+    /// The watched storage backend.
+    backend: Arc<dyn Backend>,
+    /// The watched location.
+    location: Location,
+    /// Every resource as of the last poll.
+    seen: Resources<T>,
+}
+
+impl<T: Stored> Watcher<T>
+where
+    T::Arguments: Clone,
+{
+    /// Creates a new [`Watcher<T>`] within the current storage [`backend`](<crate::backend>).
+    ///
+    /// Resources that already exist are not reported as created.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the backend could not be traversed or read.
+    pub fn new() -> Result<Self, Error<T::Format>> {
+        let location = Location::new(T::LOCATION);
+
+        self::acquire(&WATCHED).push(location.clone());
+
+        // From here on, dropping the watcher will unregister its location.
+        let mut watcher = Self { backend: crate::backend(), location, seen: BTreeMap::new() };
+
+        watcher.seen = watcher.scan()?;
+
+        Ok(watcher)
+    }
+
+    /// Returns every resource that is currently stored.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the backend could not be traversed or read.
+    fn scan(&self) -> Result<Resources<T>, Error<T::Format>> {
+        let mut found = BTreeMap::new();
+
+        for (captures, path) in self.location.walk(&(*self.backend))? {
+            let components = captures.iter().map(|c| &(**c)).collect::<Box<[_]>>();
+            let Some(arguments) = T::arguments(&components) else {
+                continue;
+            };
+
+            // This ensures that the file's extension matches the expected format.
+            if T::stored(arguments.clone()).path() != &(*path) {
+                continue;
+            }
+
+            match self.backend.read(&path) {
+                Ok(bytes) => drop(found.insert(path, (arguments, self::digest(&bytes)))),
+                // The resource may have been removed since the backend was traversed.
+                Err(error) if error.kind() == ErrorKind::NotFound => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        Ok(found)
+    }
+
+    /// Returns every change made outside of this process since the last poll, sorted by path.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the backend could not be traversed or read.
+    pub fn poll(&mut self) -> Result<Vec<Event<T>>, Error<T::Format>> {
+        let current = self.scan()?;
+        let previous = std::mem::replace(&mut self.seen, current);
+        let paths = previous.keys().chain(self.seen.keys()).collect::<BTreeSet<_>>();
+        let written = self::acquire(&WRITTEN);
+        let cache = T::cache();
+        let mut events = vec![];
+
+        for path in paths {
+            let (previous, current) = (previous.get(path), self.seen.get(path));
+            let (change, arguments) = match (previous, current) {
+                (Some((_, a)), Some((_, b))) if a == b => continue,
+                (Some(_), Some((arguments, _))) => (Change::Modified, arguments),
+                (None, Some((arguments, _))) => (Change::Created, arguments),
+                (Some((arguments, _)), None) => (Change::Removed, arguments),
+                (None, None) => continue,
+            };
+            let digest = current.map(|(_, digest)| *digest);
+
+            let key = self::resource_key(&self.backend, path);
+
+            if key.is_some_and(|k| written.get(&k) == Some(&digest)) {
+                continue;
+            }
+            if let Some(cache) = &cache {
+                cache.invalidate(&self.backend, path);
+            }
+
+            let key = T::stored(arguments.clone());
+
+            events.push(Event { change, arguments: arguments.clone(), key });
+        }
+
+        drop(written);
+
+        Ok(events)
+    }
+}
+
+impl<T: Stored> Debug for Watcher<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Arguments are intentionally omitted, as they may not be printable.
+        f.debug_struct("Watcher")
+            .field("location", &self.location)
+            .field("seen", &self.seen.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Stored> Drop for Watcher<T> {
+    fn drop(&mut self) {
+        let mut watched = self::acquire(&WATCHED);
+
+        if let Some(index) = watched.iter().position(|l| l == &self.location) {
+            watched.swap_remove(index);
+        }
+    }
+}
@@ -214,6 +214,17 @@ impl<'api: 'evt, 'evt, T: Send> Ctx<'api, 'evt, T> {
     pub async fn failure(self, locale: Locale, key: impl Display + Send, has_desc: bool) -> Result {
         self.complete(locale, "failure", key, FAILURE, has_desc).await
     }
+
+    /// Responds to the interaction with a failure message stating that the guild's storage quota
+    /// was exceeded.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the interaction could not be responded to.
+    #[inline]
+    pub async fn quota_exceeded(self, locale: Locale) -> Result {
+        self.failure(locale, "quota_exceeded", true).await
+    }
 }
 
 /// Responds to an interaction event.
@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::bail;
 use doop_localizer::localize;
@@ -18,6 +19,7 @@ use twilight_model::channel::message::MessageFlags;
 use twilight_model::gateway::payload::incoming::{InteractionCreate, Ready};
 use twilight_model::gateway::payload::outgoing::update_presence::UpdatePresencePayload;
 use twilight_model::gateway::presence::{ActivityType, MinimalActivity, Status};
+use twilight_model::id::Id;
 use twilight_util::builder::embed::EmbedBuilder;
 
 use crate::bot::client::{Api, ApiRef};
@@ -89,6 +91,8 @@ impl BotClient {
     ///
     /// This function will return an error if the bot encounters an unhandled exception.
     pub async fn start(mut self) -> Result {
+        let interval = crate::util::arguments().data_watch_interval.unwrap_or(5);
+        let watcher = tokio::spawn(watch_task(self.api.clone(), Duration::from_secs(interval)));
         let mut stream = ShardEventStream::new(self.shards.iter_mut());
         let mut tasks = JoinSet::new();
 
@@ -101,6 +105,7 @@ impl BotClient {
         }
 
         drop(stream);
+        watcher.abort();
 
         while tasks.join_next().await.is_some() {}
 
@@ -108,6 +113,19 @@ impl BotClient {
     }
 }
 
+/// Watches stored data for changes made outside of the bot.
+///
+/// # Errors
+///
+/// This function will return an error if log(s) failed to output.
+async fn watch_task(api: Api, interval: Duration) -> Result {
+    if let Err(error) = crate::cmd::membership::watch(api, interval).await {
+        warn!("data watching failed: {error}")?;
+    }
+
+    Ok(())
+}
+
 /// Handles an incoming API event by spawning a new task.
 ///
 /// # Errors
@@ -185,7 +203,10 @@ async fn on_ready(api: Api, event: Ready) -> Result {
 ///
 /// This function will return an error if the event could not be handled.
 async fn on_interaction(api: ApiRef<'_>, event: InteractionCreate) -> Result {
-    info!("received interaction: {}", event.marker())?;
+    let (kind, id) = (event.kind_name(), event.id.get());
+    let (guild, user) = (event.guild_id.map(Id::get), event.author_id().map(Id::get));
+
+    info!("received interaction"; kind, id, guild, user)?;
 
     let result: Result = match event.kind {
         InteractionType::ApplicationCommand => self::on_command(api, &event).await,
@@ -196,10 +217,10 @@ async fn on_interaction(api: ApiRef<'_>, event: InteractionCreate) -> Result {
     };
 
     if let Err(ref error) = result {
-        warn!("failed interaction: {} - {error}", event.marker())?;
+        warn!("failed interaction"; kind, id, guild, user, error = %error)?;
         self::on_error(api, &event, error).await?;
     } else {
-        info!("succeeded interaction: {}", event.marker())?;
+        info!("succeeded interaction"; kind, id, guild, user)?;
     }
 
     result
@@ -0,0 +1,152 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use doop_logger::{info, warn};
+use doop_storage::{ArchiveOptions, Compress, MsgPack, Registry, Snapshot};
+
+use crate::util::{arguments, Command, Result, StorageCommand};
+
+/// Runs the given command-line subcommand.
+///
+/// # Errors
+///
+/// This function will return an error if the subcommand fails.
+pub fn run(command: &Command) -> Result {
+    match command {
+        Command::Backup { path, compress, encrypt } => self::backup(path, *compress, *encrypt),
+        Command::Restore { path } => self::restore(path),
+        Command::Storage { command: StorageCommand::Verify { quarantine } } => {
+            self::verify(quarantine.as_deref())
+        }
+        Command::Storage { command: StorageCommand::Dump { location } } => self::dump(location),
+        Command::Storage { command: StorageCommand::Load { location, path } } => {
+            self::load(location, path.as_deref())
+        }
+        Command::Storage { command: StorageCommand::RotateKeys } => self::rotate_keys(),
+    }
+}
+
+/// Saves a snapshot of the installed storage backend into the archive at the given path.
+///
+/// # Errors
+///
+/// This function will return an error if the snapshot could not be captured or saved.
+fn backup(path: &Path, compress: bool, encrypt: bool) -> Result {
+    let snapshot = Snapshot::capture(&(*doop_storage::backend()), self::data_filter())?;
+
+    snapshot.save(path, ArchiveOptions { compress, encrypt })?;
+
+    let count = snapshot.manifest().entries.len();
+
+    Ok(info!("saved {count} entries into '{}'", path.display())?)
+}
+
+/// Restores the archive at the given path into the installed storage backend.
+///
+/// # Errors
+///
+/// This function will return an error if the archive could not be loaded or verified, if the bot is
+/// running, or if the storage backend is not empty.
+fn restore(path: &Path) -> Result {
+    let snapshot = Snapshot::load(path)?;
+    let _instance = crate::util::lock_instance()?;
+
+    snapshot.restore(&(*doop_storage::backend()), self::data_filter())?;
+
+    let count = snapshot.manifest().entries.len();
+
+    Ok(info!("restored {count} entries from '{}'", path.display())?)
+}
+
+/// Returns a filter that excludes every file within the storage directory that is not stored data,
+/// such as the localization files.
+fn data_filter() -> impl FnMut(&Path) -> bool {
+    let arguments = arguments();
+    let dir = arguments.data_dir.clone().unwrap_or_else(|| PathBuf::from("res").into());
+    let lang = arguments.l18n_map_dir.clone().unwrap_or_else(|| dir.join("lang").into());
+
+    move |path| !dir.join(path).starts_with(&lang)
+}
+
+/// Returns a registry containing the location of every type that the bot stores.
+fn registry() -> Registry {
+    // Component data is stored by the `DataId` type, and may be of any type.
+    let registry = Registry::new().with_location::<Compress<MsgPack, 4>>(".cid/{}/{}/{}/{}");
+    let registry = crate::cmd::membership::register_stored(registry);
+
+    crate::cmd::role::register_stored(registry)
+}
+
+/// Verifies every file within the installed storage backend, moving each file that failed
+/// verification into the given quarantine directory if present.
+///
+/// # Errors
+///
+/// This function will return an error if the storage backend could not be verified, or if a file
+/// could not be quarantined.
+fn verify(quarantine: Option<&Path>) -> Result {
+    let report = self::registry().verify(self::data_filter())?;
+
+    for (path, issue) in &report.issues {
+        warn!("{issue}: '{}'", path.display())?;
+    }
+
+    info!("verified {} entries, {} failed", report.checked, report.issues.len())?;
+
+    if let Some(quarantine) = quarantine {
+        let moved = report.quarantine(quarantine)?;
+
+        info!("quarantined {} entries into '{}'", moved.len(), quarantine.display())?;
+    }
+
+    Ok(())
+}
+
+/// Prints the decoded contents of the stored file at the given location as JSON.
+///
+/// # Errors
+///
+/// This function will return an error if the location is unknown, or if the file could not be read
+/// or decoded.
+fn dump(location: &str) -> Result {
+    let value = self::registry().dump(location)?;
+
+    println!("{}", serde_json::to_string_pretty(&value)?);
+
+    Ok(())
+}
+
+/// Encodes the JSON within the given file, or the standard input if absent, into the stored file at
+/// the given location.
+///
+/// # Errors
+///
+/// This function will return an error if the JSON could not be read, if the bot is running, if the
+/// location is unknown, or if the JSON is not valid for the location's type.
+fn load(location: &str, path: Option<&Path>) -> Result {
+    let reader: Box<dyn Read> = match path {
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => Box::new(std::io::stdin().lock()),
+    };
+    let value = serde_json::from_reader(reader)?;
+    // The running bot caches and indexes values in memory, so it must not be running.
+    let _instance = crate::util::lock_instance()?;
+
+    self::registry().load(location, &value)?;
+
+    Ok(info!("loaded '{location}'")?)
+}
+
+/// Re-encrypts every encrypted stored file using the active key of the storage encryption keyring.
+///
+/// # Errors
+///
+/// This function will return an error if the bot is running, or if any file could not be read or
+/// re-encrypted.
+fn rotate_keys() -> Result {
+    let _instance = crate::util::lock_instance()?;
+    let rotated = self::registry().rotate()?;
+
+    Ok(info!("re-encrypted {rotated} entries using the active key")?)
+}
@@ -0,0 +1,89 @@
+use anyhow::bail;
+use doop_localizer::localize;
+use doop_storage::Quota;
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use crate::bot::interaction::CommandCtx;
+use crate::cmd::{CommandEntry, CommandOptionResolver, OnCommand};
+use crate::util::traits::PreferLocale;
+use crate::util::{Result, BRANDING};
+
+crate::register_command! {
+    ChatInput("data") {
+        let in_dms = false;
+        let is_nsfw = false;
+        let require = ADMINISTRATOR;
+        let options = [
+            SubCommand("usage") {},
+        ];
+        let handlers = {
+            command = self::execute_command;
+        };
+    }
+}
+
+/// The units used when displaying a size in bytes.
+const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+/// Returns the given size in bytes as a human-readable string.
+#[allow(clippy::cast_precision_loss)] // only used for display.
+fn size(bytes: u64) -> String {
+    let mut value = bytes as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} {}", UNITS[0])
+    } else {
+        format!("{value:.2} {}", UNITS[unit])
+    }
+}
+
+async fn execute_command<'api: 'evt, 'evt>(
+    cmd: &(dyn OnCommand + Send + Sync),
+    mut ctx: CommandCtx<'api, 'evt>,
+) -> Result {
+    ctx.defer(true).await?;
+
+    let resolver = CommandOptionResolver::new(ctx.data);
+
+    if resolver.get_subcommand("usage").is_ok() {
+        return self::usage(cmd.entry(), ctx).await;
+    }
+
+    bail!("unknown or missing subcommand");
+}
+
+async fn usage<'api: 'evt, 'evt>(entry: &CommandEntry, ctx: CommandCtx<'api, 'evt>) -> Result {
+    let Some(guild_id) = ctx.event.guild_id else {
+        bail!("command must be used within a guild");
+    };
+
+    let locale = ctx.event.author().preferred_locale();
+    let usage = doop_storage::usage(&guild_id.to_string())?;
+    let Quota { bytes, keys } = doop_storage::quota();
+    let unlimited = localize!(try in locale, "text.{}.unlimited", entry.name);
+
+    let bytes = bytes.map_or_else(|| unlimited.to_string(), self::size);
+    let bytes = format!("{} / {bytes}", self::size(usage.bytes));
+    let keys = keys.map_or_else(|| unlimited.to_string(), |k| k.to_string());
+    let keys = format!("{} / {keys}", usage.keys);
+
+    let embed = EmbedBuilder::new()
+        .color(BRANDING)
+        .title(localize!(try in locale, "text.{}.usage", entry.name))
+        .field(EmbedFieldBuilder::new(localize!(try in locale, "text.{}.bytes", entry.name), bytes))
+        .field(EmbedFieldBuilder::new(localize!(try in locale, "text.{}.keys", entry.name), keys))
+        .build();
+
+    crate::followup!(as ctx => {
+        let embeds = &[embed];
+    })
+    .await?;
+
+    Ok(())
+}
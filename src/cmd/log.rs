@@ -0,0 +1,71 @@
+use anyhow::bail;
+use doop_logger::{info, Level, Sink};
+
+use crate::bot::interaction::CommandCtx;
+use crate::cmd::{CommandEntry, CommandOptionResolver, OnCommand};
+use crate::util::traits::PreferLocale;
+use crate::util::Result;
+
+crate::register_command! {
+    #[developer(true)]
+    ChatInput("log") {
+        let in_dms = false;
+        let is_nsfw = false;
+        let require = ADMINISTRATOR;
+        let options = [
+            SubCommand("level") {
+                let options = [
+                    String("sink") {
+                        let required = true;
+                        let choices = [("console", "console"), ("file", "file")];
+                    },
+                    String("minimum") {
+                        let required = true;
+                        let choices = [
+                            ("debug", "debug"),
+                            ("info", "info"),
+                            ("warn", "warn"),
+                            ("error", "error"),
+                        ];
+                    },
+                ];
+            },
+        ];
+        let handlers = {
+            command = self::execute_command;
+        };
+    }
+}
+
+async fn execute_command<'api: 'evt, 'evt>(
+    cmd: &(dyn OnCommand + Send + Sync),
+    mut ctx: CommandCtx<'api, 'evt>,
+) -> Result {
+    ctx.defer(true).await?;
+
+    let resolver = CommandOptionResolver::new(ctx.data);
+
+    if let Ok(resolver) = resolver.get_subcommand("level") {
+        return self::level(cmd.entry(), ctx, resolver).await;
+    }
+
+    bail!("unknown or missing subcommand");
+}
+
+async fn level<'api: 'evt, 'evt>(
+    entry: &CommandEntry,
+    ctx: CommandCtx<'api, 'evt>,
+    resolver: CommandOptionResolver<'evt>,
+) -> Result {
+    let sink = match resolver.get_str("sink")? {
+        "console" => Sink::Console,
+        "file" => Sink::File,
+        sink => bail!("invalid log sink '{sink}'"),
+    };
+    let level = resolver.get_str("minimum")?.parse::<Level>().map_err(anyhow::Error::msg)?;
+
+    doop_logger::set_level(sink, level)?;
+    info!("updated minimum log level"; sink = ?sink, level = level.name)?;
+
+    ctx.success(ctx.event.preferred_locale(), format!("{}.updated", entry.name), false).await
+}
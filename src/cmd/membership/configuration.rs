@@ -19,9 +19,18 @@ use crate::util::traits::{IntoImageSource, PreferLocale};
 use crate::util::{Anchor, DataId, Result, BRANDING};
 
 /// A guild's membership configuration.
+///
+/// Configurations are stored as `membership/{guild}/config.toml` within the data directory. As
+/// the type is versioned, a stored file holds a top-level `version` key and the configuration
+/// itself within a `[data]` table. Hand-written files without this layout are read as version `0`
+/// and are wrapped in it when they are next written; when editing a stored file by hand, only the
+/// `[data]` table should be changed.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Storage)]
 #[format(Toml)]
-#[location("{}/{}/config", &'static str, Id<GuildMarker>)]
+#[location("membership/{}/config", Id<GuildMarker>)]
+#[namespace(0)]
+#[version(0)]
+#[cache(64)]
 pub struct Config {
     /// The guild's identifier.
     pub id: Id<GuildMarker>,
@@ -102,7 +111,7 @@ impl Config {
         let custom_id = DataId::new(entry.name, "application");
         let mut modal = ModalBuilder::new(custom_id, title);
 
-        let past = Submission::stored((entry.name, self.id, user_id));
+        let past = Submission::stored((self.id, user_id));
         let submission = past.read().ok().map(Value::get_owned);
 
         for (index, question) in self.submission.questions.iter().enumerate() {
@@ -177,7 +186,7 @@ impl TryFrom<&CommandOptionResolver<'_>> for ConfigSubmission {
         let questions = (1_u8 ..= 5_u8)
             .map(|n| format!("question_{n}"))
             .filter_map(|s| resolver.get_str(&s).ok().map(Box::from))
-            .collect::<Box<_>>();
+            .collect::<Box<[_]>>();
 
         if questions.is_empty() {
             bail!("at least one question must be provided");
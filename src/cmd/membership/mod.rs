@@ -1,8 +1,13 @@
+use std::time::Duration;
+
 use anyhow::bail;
-use doop_storage::{Stored, Value};
+use doop_logger::{info, warn};
+use doop_storage::{CacheStats, Change, Event, Registry, Stored, Watcher};
 use twilight_model::application::command::{CommandOptionChoice, CommandOptionType};
 
+use crate::bot::client::Api;
 use crate::bot::interaction::{CommandCtx, ComponentCtx, ModalCtx};
+use crate::cmd::membership::configuration::Config;
 use crate::cmd::membership::submission::{StatusKind, Submission};
 use crate::cmd::{CommandOptionResolver, OnCommand, OnComplete, OnComponent, OnModal};
 use crate::util::{DataId, Result};
@@ -15,11 +20,109 @@ mod on {
     pub mod complete;
     pub mod component;
     pub mod modal;
+    pub mod watch;
 }
 
 /// The number of defined entry toast titles.
 pub const ENTRY_TOASTS: usize = 10;
 
+/// Registers the locations of the membership command's stored types within the given registry.
+#[must_use]
+pub fn register_stored(registry: Registry) -> Registry {
+    registry.with::<Config>().with::<Submission>()
+}
+
+/// Rebuilds the indexes of the membership command's stored types if any are missing or outdated,
+/// returning the number of indexed entries if they were rebuilt.
+///
+/// # Errors
+///
+/// This function will return an error if any index could not be rebuilt.
+pub fn reindex_stored() -> Result<Option<usize>> {
+    Ok(Submission::reindex_stale()?)
+}
+
+/// Accounts for the membership command's stored types within their guilds' storage usage,
+/// returning the number of accounted entries.
+///
+/// # Errors
+///
+/// This function will return an error if any entry could not be accounted for.
+pub fn account_stored() -> Result<usize> {
+    Ok(Config::account()? + Submission::account()?)
+}
+
+/// Watches the membership configurations for changes made outside of the bot, such as by hand,
+/// re-rendering the entrypoint of each changed guild.
+///
+/// # Errors
+///
+/// This function will return an error if the configurations could not be watched. Failed polls are
+/// logged and retried on the next tick.
+pub async fn watch(api: Api, interval: Duration) -> Result {
+    let Some(entry) = crate::cmd::registry().get("membership") else {
+        bail!("missing command 'membership'");
+    };
+    let mut watcher = Watcher::<Config>::new()?;
+    let mut interval = tokio::time::interval(interval);
+
+    loop {
+        interval.tick().await;
+
+        // Polling traverses the storage backend, so it's moved off of the async runtime.
+        let (returned, polled) = tokio::task::spawn_blocking(move || {
+            let polled = watcher.poll();
+
+            (watcher, polled)
+        })
+        .await?;
+
+        watcher = returned;
+
+        let events = match polled {
+            Ok(events) => events,
+            Err(error) => {
+                warn!("failed to poll membership configurations: {error}")?;
+
+                continue;
+            }
+        };
+
+        for Event { change, arguments: guild_id, key } in events {
+            if change == Change::Removed {
+                continue;
+            }
+
+            info!("reloaded membership configuration for guild {guild_id}")?;
+
+            if let Err(error) = self::on::watch::config(entry, api.into_ref(), &key).await {
+                warn!("failed to refresh membership entrypoint for guild {guild_id}: {error}")?;
+            }
+        }
+    }
+}
+
+/// Returns the statistics of the membership command's stored type caches, alongside their names.
+///
+/// Configurations are watched, while submissions are only cached once the bot has written them,
+/// as they may otherwise be changed outside of the bot unnoticed.
+pub fn cache_stats() -> impl Iterator<Item = (&'static str, CacheStats)> {
+    let config = Config::cache().map(|c| ("membership configuration", c.stats()));
+    let submission = Submission::cache().map(|c| ("membership submission", c.stats()));
+
+    config.into_iter().chain(submission)
+}
+
+/// Clears the membership command's stored type caches.
+pub fn clear_caches() {
+    if let Some(cache) = Config::cache() {
+        cache.clear();
+    }
+    if let Some(cache) = Submission::cache() {
+        cache.clear();
+    }
+}
+
 crate::register_command! {
     ChatInput("membership") {
         let in_dms = false;
@@ -160,23 +263,20 @@ async fn execute_complete<'api: 'evt, 'evt>(
     let ("user", CommandOptionType::String) = (name, kind) else {
         return Ok(vec![]);
     };
+    let Some(guild_id) = ctx.event.guild_id else {
+        bail!("command must be used in a guild");
+    };
 
     if resolver.get_subcommand("update").is_ok() {
-        return self::on::complete::member(acp.entry(), ctx, value, |guild_id, member| {
-            let submission = Submission::stored((acp.entry().name, guild_id, member.user.id));
-            let Ok(submission) = submission.read().map(Value::get_owned) else {
-                return true;
-            };
+        let submissions = Submission::query_within(guild_id, "status.kind", &StatusKind::Pending)?;
 
-            submission.status.kind == StatusKind::Pending
-        })
-        .await;
+        return self::on::complete::member(ctx, value, submissions).await;
     }
     if resolver.get_subcommand("discard").is_ok() {
-        return self::on::complete::member(acp.entry(), ctx, value, |_, _| true).await;
+        return self::on::complete::member(ctx, value, Submission::list_within(guild_id)?).await;
     }
     if resolver.get_subcommand("view").is_ok() {
-        return self::on::complete::member(acp.entry(), ctx, value, |_, _| true).await;
+        return self::on::complete::member(ctx, value, Submission::list_within(guild_id)?).await;
     }
 
     bail!("unknown or missing subcommand");
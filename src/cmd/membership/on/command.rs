@@ -13,7 +13,7 @@ use crate::cmd::membership::configuration::Config;
 use crate::cmd::membership::submission::{StatusKind, StatusUpdate, Submission};
 use crate::cmd::{CommandEntry, CommandOptionResolver};
 use crate::util::extension::EmbedAuthorExtension;
-use crate::util::traits::PreferLocale;
+use crate::util::traits::{PreferLocale, WithinQuota};
 use crate::util::{Anchor, Result, FAILURE, SUCCESS};
 
 pub async fn configure<'api: 'evt, 'evt>(
@@ -29,12 +29,12 @@ pub async fn configure<'api: 'evt, 'evt>(
     };
 
     let locale = ctx.event.preferred_locale();
-    let key = Config::stored((entry.name, guild_id));
-    let previous = key.read().ok().map(Value::get_owned);
+    let key = Config::stored(guild_id);
+    let previous = key.read_async().await.ok().map(Value::get_owned);
     let mut config = Config::new(guild_id, &resolver, previous.as_ref())?;
     let (embed, components) = config.build_entrypoint(entry, ctx.api).await?;
 
-    if let Some(mut anchor) = key.read().ok().and_then(|v| v.get_owned().anchor) {
+    if let Some(mut anchor) = key.read_async().await.ok().and_then(|v| v.get_owned().anchor) {
         if anchor.fetch(ctx.api).await.is_ok() {
             anchor.update(ctx.api).embeds(Some(&[embed]))?.components(Some(&components))?.await?;
         } else {
@@ -52,7 +52,9 @@ pub async fn configure<'api: 'evt, 'evt>(
         config.anchor = Some(Anchor::from(message));
     }
 
-    key.write(&config)?;
+    if key.write_async(&config).await.within_quota()?.is_none() {
+        return ctx.quota_exceeded(locale).await;
+    }
 
     ctx.success(locale, format!("{}.configured", entry.name), false).await
 }
@@ -72,9 +74,9 @@ pub async fn update<'api: 'evt, 'evt>(
         return ctx.failure(locale, format!("{}.invalid_user", entry.name), false).await;
     };
 
-    let submission = Submission::stored((entry.name, guild_id, user_id));
+    let submission = Submission::stored((guild_id, user_id));
 
-    if let Ok(submission) = submission.read() {
+    if let Ok(submission) = submission.read_async().await {
         if submission.get().status.kind != StatusKind::Pending {
             return ctx.failure(locale, format!("{}.not_pending", entry.name), false).await;
         }
@@ -99,46 +101,68 @@ pub async fn discard<'api: 'evt, 'evt>(
         return ctx.failure(locale, format!("{}.invalid_user", entry.name), false).await;
     };
 
-    let config = Config::stored((entry.name, guild_id));
-    let Ok(config) = config.read() else {
+    let config = Config::stored(guild_id);
+    let Ok(config) = config.read_async().await else {
         return ctx.failure(locale, format!("{}.no_config", entry.name), false).await;
     };
 
-    let submission = Submission::stored((entry.name, guild_id, user_id));
-    let Ok(mut submission) = submission.read() else {
+    let key = Submission::stored((guild_id, user_id));
+
+    if !key.exists_async().await? {
         return ctx.failure(locale, format!("{}.no_entry", entry.name), false).await;
-    };
-    if submission.get().status.kind == StatusKind::Discarded {
-        return ctx.failure(locale, format!("{}.discarded", entry.name), false).await;
     }
 
-    submission.get_mut().update_status(
-        StatusKind::Discarded,
-        StatusUpdate {
-            author_id: user_id,
-            timestamp: OffsetDateTime::now_utc(),
-            reason: None,
-            comment: None,
-            previous: None,
-        },
-    );
+    // The submission is only written if it changes, so discarding it twice is a no-op.
+    let submission = key
+        .locked_async(move |key| {
+            let mut submission = key.read()?.get_owned();
+
+            if submission.status.kind == StatusKind::Discarded {
+                return Ok(None);
+            }
+
+            submission.update_status(
+                StatusKind::Discarded,
+                StatusUpdate {
+                    author_id: user_id,
+                    timestamp: OffsetDateTime::now_utc(),
+                    reason: None,
+                    comment: None,
+                    previous: None,
+                },
+            );
+
+            key.write(&submission)?;
+
+            Ok(Some(submission))
+        })
+        .await;
+    let Some(submission) = submission.within_quota()? else {
+        return ctx.quota_exceeded(locale).await;
+    };
+    let Some(submission) = submission else {
+        return ctx.failure(locale, format!("{}.discarded", entry.name), false).await;
+    };
 
-    let (embed, components) = submission.get().build_form(entry, ctx.api).await?;
+    let (embed, components) = submission.build_form(entry, ctx.api).await?;
 
-    if let Some(anchor) = submission.get().anchor {
+    if let Some(anchor) = submission.anchor {
         let message = anchor.update(ctx.api).components(Some(&components))?;
 
         message.embeds(Some(&[embed]))?.await?.model().await?;
     } else {
         let message = ctx.api.http.create_message(config.get().submission.output_channel_id);
         let message = message.components(&components)?.embeds(&[embed])?.await?.model().await?;
+        let anchor = Anchor::from(message);
 
-        submission.get_mut().anchor = Some(Anchor::from(message));
-    }
+        let result = key.update_async(move |submission| submission.anchor = Some(anchor)).await;
 
-    submission.write()?;
+        if result.within_quota()?.is_none() {
+            return ctx.quota_exceeded(locale).await;
+        }
+    }
 
-    let key = submission.get().status.kind.localization_key();
+    let key = submission.status.kind.localization_key();
     ctx.success(locale, format!("{}.updated_{key}", entry.name), false).await
 }
 
@@ -156,8 +180,8 @@ pub async fn view<'api: 'evt, 'evt>(
         return ctx.failure(locale, format!("{}.invalid_user", entry.name), false).await;
     };
 
-    let submission = Submission::stored((entry.name, guild_id, user_id));
-    let Ok(submission) = submission.read() else {
+    let submission = Submission::stored((guild_id, user_id));
+    let Ok(submission) = submission.read_async().await else {
         return ctx.failure(locale, format!("{}.no_entry", entry.name), false).await;
     };
 
@@ -188,18 +212,25 @@ pub async fn active<'api: 'evt, 'evt>(
 
     let locale = ctx.event.preferred_locale();
     let state = *resolver.get_bool("state")?;
-    let config = Config::stored((entry.name, guild_id));
-    let Ok(mut config) = config.read() else {
+    let config = Config::stored(guild_id);
+
+    if !config.exists_async().await? {
         return ctx.failure(locale, format!("{}.no_config", entry.name), false).await;
-    };
+    }
 
-    config.get_mut().entrypoint.open = state;
-    config.write()?;
+    let channel_id = config
+        .update_async(move |config| {
+            config.entrypoint.open = state;
+            config.submission.output_channel_id
+        })
+        .await;
+    let Some(channel_id) = channel_id.within_quota()? else {
+        return ctx.quota_exceeded(locale).await;
+    };
 
     let (color, key) = if state { (SUCCESS, "on") } else { (FAILURE, "off") };
     let title = localize!(try in locale, "success.{}.active_{key}.title", entry.name);
     let embed = EmbedBuilder::new().author(EmbedAuthor::parse(user)?).color(color).title(title);
-    let channel_id = config.get().submission.output_channel_id;
 
     ctx.api.http.create_message(channel_id).embeds(&[embed.build()])?.await?;
     ctx.success(locale, format!("{}.active_{key}", entry.name), false).await
@@ -1,17 +1,14 @@
-use std::num::NonZeroU64;
-
 use anyhow::bail;
 use doop_localizer::localize;
-use doop_storage::{Stored, Value};
+use doop_storage::{Listing, Stored, Value};
 use twilight_model::application::command::{
     CommandOptionChoice, CommandOptionChoiceValue, CommandOptionType,
 };
 use twilight_model::guild::Member;
-use twilight_model::id::marker::GuildMarker;
-use twilight_model::id::Id;
 
 use crate::bot::interaction::CommandCtx;
 use crate::cmd::membership::configuration::Config;
+use crate::cmd::membership::submission::Submission;
 use crate::cmd::CommandEntry;
 use crate::util::extension::UserExtension;
 use crate::util::traits::PreferLocale;
@@ -25,7 +22,7 @@ pub fn configuration<'api: 'evt, 'evt>(
     let Some(guild_id) = ctx.event.guild_id else {
         bail!("command must be used within a guild");
     };
-    let Ok(config) = Config::stored((entry.name, guild_id)).read().map(Value::get_owned) else {
+    let Ok(config) = Config::stored(guild_id).read().map(Value::get_owned) else {
         return Ok(vec![]);
     };
 
@@ -67,10 +64,9 @@ pub fn configuration<'api: 'evt, 'evt>(
 }
 
 pub async fn member<'api: 'evt, 'evt>(
-    entry: &CommandEntry,
     ctx: CommandCtx<'api, 'evt>,
     query: &str,
-    predicate: impl Send + Sync + Fn(Id<GuildMarker>, &Member) -> bool,
+    submissions: Listing<Submission>,
 ) -> Result<Vec<CommandOptionChoice>> {
     #[inline]
     fn matches_query(member: &Member, query: &str) -> bool {
@@ -95,22 +91,11 @@ pub async fn member<'api: 'evt, 'evt>(
 
     let query = query.to_lowercase();
     let guild = ctx.api.http.guild(guild_id).await?.model().await?;
-    let dir = doop_storage::directory().join(entry.name).join(guild_id.to_string());
     let mut options = vec![];
 
-    for entry in std::fs::read_dir(dir)?.filter_map(Result::ok) {
-        if !entry.metadata().is_ok_and(|m| m.is_file()) {
-            continue;
-        }
-        let Some(stem) = entry.path().file_stem().map(|s| s.to_string_lossy().into_owned()) else {
-            continue;
-        };
-        let Ok(user_id) = stem.parse::<NonZeroU64>().map(Id::from) else {
-            continue;
-        };
-
+    for ((_, user_id), _) in submissions {
         if let Some(member) = guild.members.iter().find(|m| m.user.id == user_id) {
-            if predicate(guild_id, member) && matches_query(member, &query) {
+            if matches_query(member, &query) {
                 options.push(create_choice(member));
             }
         } else {
@@ -121,7 +106,7 @@ pub async fn member<'api: 'evt, 'evt>(
                 continue;
             };
 
-            if predicate(guild_id, &member) && matches_query(&member, &query) {
+            if matches_query(&member, &query) {
                 options.push(create_choice(&member));
             }
         }
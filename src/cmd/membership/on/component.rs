@@ -36,12 +36,12 @@ pub async fn apply<'api: 'evt, 'evt>(
     };
 
     let locale = ctx.event.preferred_locale();
-    let config = &Config::stored((entry.name, guild_id));
-    let Ok(config) = config.read() else {
+    let config = &Config::stored(guild_id);
+    let Ok(config) = config.read_async().await else {
         return ctx.failure(locale, format!("{}.no_config", entry.name), false).await;
     };
 
-    if let Ok(submission) = Submission::stored((entry.name, guild_id, user_id)).read() {
+    if let Ok(submission) = Submission::stored((guild_id, user_id)).read_async().await {
         let status = submission.get().status.kind;
 
         if matches!(status, StatusKind::Pending | StatusKind::Accepted | StatusKind::Rejected) {
@@ -78,8 +78,8 @@ pub async fn update<'api: 'evt, 'evt>(
     let status = StatusKind::try_from(status.parse::<i64>()?)?;
 
     let locale = ctx.event.preferred_locale();
-    let submission = Submission::stored((entry.name, guild_id, user_id));
-    let Ok(submission) = submission.read() else {
+    let submission = Submission::stored((guild_id, user_id));
+    let Ok(submission) = submission.read_async().await else {
         return ctx.failure(locale, format!("{}.no_entry", entry.name), false).await;
     };
 
@@ -104,8 +104,8 @@ pub async fn entries<'api: 'evt, 'evt>(
     let user = ctx.api.http.user(user_id).await?.model().await?;
 
     let locale = ctx.event.preferred_locale();
-    let submission = Submission::stored((entry.name, guild_id, user_id));
-    let Ok(submission) = submission.read().map(Value::get_owned) else {
+    let submission = Submission::stored((guild_id, user_id));
+    let Ok(submission) = submission.read_async().await.map(Value::get_owned) else {
         return ctx.failure(locale, format!("{}.no_entry", entry.name), false).await;
     };
 
@@ -171,8 +171,8 @@ pub async fn updates<'api: 'evt, 'evt>(
     let user = ctx.api.http.user(user_id).await?.model().await?;
 
     let locale = ctx.event.preferred_locale();
-    let submission = Submission::stored((entry.name, guild_id, user_id));
-    let Ok(submission) = submission.read().map(Value::get_owned) else {
+    let submission = Submission::stored((guild_id, user_id));
+    let Ok(submission) = submission.read_async().await.map(Value::get_owned) else {
         return ctx.failure(locale, format!("{}.no_entry", entry.name), false).await;
     };
 
@@ -13,7 +13,7 @@ use crate::cmd::membership::submission::{
     Status, StatusKind, StatusUpdate, Submission, SubmissionArchive,
 };
 use crate::cmd::{CommandEntry, ModalFieldResolver};
-use crate::util::traits::PreferLocale;
+use crate::util::traits::{PreferLocale, WithinQuota};
 use crate::util::{Anchor, DataId, Result};
 
 pub async fn application<'api: 'evt, 'evt>(
@@ -33,13 +33,13 @@ pub async fn application<'api: 'evt, 'evt>(
     };
 
     let locale = Locale::get(locale).unwrap_or_else(|| *localizer().preferred_locale());
-    let config = Config::stored((entry.name, guild_id));
-    let Ok(config) = config.read() else {
+    let config = Config::stored(guild_id);
+    let Ok(config) = config.read_async().await else {
         return ctx.failure(locale, format!("{}.no_config", entry.name), false).await;
     };
 
     let resolver = ModalFieldResolver::new(ctx.data);
-    let key = Submission::stored((entry.name, guild_id, user_id));
+    let key = Submission::stored((guild_id, user_id));
     let mut submission = Submission {
         id: user_id,
         guild_id,
@@ -62,31 +62,54 @@ pub async fn application<'api: 'evt, 'evt>(
 
     submission.answers = answers.into_boxed_slice();
 
-    if let Ok(previous) = key.read().map(Value::get_owned) {
-        let mut archives = previous.archives.to_vec();
-
-        archives.push(SubmissionArchive {
-            timestamp: previous.timestamp,
-            status: previous.status.clone(),
-            answers: previous.answers,
-        });
-
-        submission.archives = archives.into_boxed_slice();
-        submission.status.update = Some(StatusUpdate {
-            author_id: user_id,
-            timestamp: OffsetDateTime::now_utc(),
-            reason: None,
-            comment: None,
-            previous: Some(Box::new(previous.status)),
-        });
+    // The previous submission is archived while holding the key's lock, so that a concurrent
+    // update to it is never lost.
+    let submission = key
+        .locked_async(move |key| {
+            if let Ok(previous) = key.read().map(Value::get_owned) {
+                let mut archives = previous.archives.to_vec();
+
+                archives.push(SubmissionArchive {
+                    timestamp: previous.timestamp,
+                    status: previous.status.clone(),
+                    answers: previous.answers,
+                });
+
+                submission.archives = archives.into_boxed_slice();
+                submission.status.update = Some(StatusUpdate {
+                    author_id: user_id,
+                    timestamp: OffsetDateTime::now_utc(),
+                    reason: None,
+                    comment: None,
+                    previous: Some(Box::new(previous.status)),
+                });
+            }
+
+            key.write(&submission)?;
+
+            Ok(submission)
+        })
+        .await;
+    let Some(mut submission) = submission.within_quota()? else {
+        return ctx.quota_exceeded(locale).await;
     };
 
     let (embed, components) = submission.build_form(entry, ctx.api).await?;
     let message = ctx.api.http.create_message(config.get().submission.output_channel_id);
     let message = message.components(&components)?.embeds(&[embed])?.await?.model().await?;
 
-    submission.anchor = Some(Anchor::from(message));
-    key.write(&submission)?;
+    let anchor = Anchor::from(message);
+
+    submission.anchor = Some(anchor);
+
+    let result = key.update_async(move |submission| submission.anchor = Some(anchor)).await;
+
+    if result.within_quota()?.is_none() {
+        // The anchor was never stored, so its message must not remain visible.
+        ctx.api.http.delete_message(anchor.channel_id, anchor.message_id).await?;
+
+        return ctx.quota_exceeded(locale).await;
+    }
 
     let status = submission.status.kind.localization_key();
     let title = localize!(try in locale, "text.{}.update_{status}.title", entry.name);
@@ -128,42 +151,52 @@ pub async fn update<'api: 'evt, 'evt>(
 
     let locale = ctx.event.preferred_locale();
 
-    let config = Config::stored((entry.name, guild_id));
-    let Ok(config) = config.read() else {
+    let config = Config::stored(guild_id);
+    let Ok(config) = config.read_async().await else {
         return ctx.failure(locale, format!("{}.no_config", entry.name), false).await;
     };
 
-    let submission = Submission::stored((entry.name, guild_id, user_id));
-    let Ok(mut submission) = submission.read() else {
+    let key = Submission::stored((guild_id, user_id));
+
+    if !key.exists_async().await? {
         return ctx.failure(locale, format!("{}.no_entry", entry.name), false).await;
-    };
+    }
 
     let mut member = ctx.api.http.guild_member(guild_id, user_id).await?.model().await?;
     let resolver = ModalFieldResolver::new(ctx.data);
+    let update = StatusUpdate {
+        author_id,
+        timestamp: OffsetDateTime::now_utc(),
+        reason: resolver.get("reason").ok().map(Into::into),
+        comment: resolver.get("comment").ok().map(Into::into),
+        previous: None,
+    };
+    let submission = key
+        .update_async(move |submission| {
+            submission.update_status(status, update);
+            submission.clone()
+        })
+        .await;
+    let Some(submission) = submission.within_quota()? else {
+        return ctx.quota_exceeded(locale).await;
+    };
 
-    submission.get_mut().update_status(
-        status,
-        StatusUpdate {
-            author_id,
-            timestamp: OffsetDateTime::now_utc(),
-            reason: resolver.get("reason").ok().map(Into::into),
-            comment: resolver.get("comment").ok().map(Into::into),
-            previous: None,
-        },
-    );
-    submission.write()?;
-
-    let (embed, components) = submission.get().build_form(entry, ctx.api).await?;
-
-    if let Some(anchor) = submission.get().anchor {
+    let (embed, components) = submission.build_form(entry, ctx.api).await?;
+
+    if let Some(anchor) = submission.anchor {
         let message = anchor.update(ctx.api).components(Some(&components))?;
 
         message.embeds(Some(&[embed]))?.await?.model().await?;
     } else {
         let message = ctx.api.http.create_message(config.get().submission.output_channel_id);
         let message = message.components(&components)?.embeds(&[embed])?.await?.model().await?;
+        let anchor = Anchor::from(message);
+
+        let result = key.update_async(move |submission| submission.anchor = Some(anchor)).await;
 
-        submission.get_mut().anchor = Some(Anchor::from(message));
+        if result.within_quota()?.is_none() {
+            return ctx.quota_exceeded(locale).await;
+        }
     }
 
     let member_role_id = config.get().submission.member_role_id;
@@ -185,7 +218,7 @@ pub async fn update<'api: 'evt, 'evt>(
         description.write_fmt(format_args!("\n\n> {comment}"))?;
     }
 
-    if submission.get().inform(ctx.api, title, description).await? {
+    if submission.inform(ctx.api, title, description).await? {
         ctx.success(locale, format!("{}.updated_{key}", entry.name), false).await
     } else {
         ctx.notify(locale, format!("{}.updated_{key}", entry.name), true).await
@@ -0,0 +1,27 @@
+use doop_storage::{Key, Toml};
+
+use crate::bot::client::ApiRef;
+use crate::cmd::membership::configuration::Config;
+use crate::cmd::CommandEntry;
+use crate::util::{Anchor, Result};
+
+pub async fn config(entry: &CommandEntry, api: ApiRef<'_>, key: &Key<Config, Toml>) -> Result {
+    let mut config = key.read_async().await?.get_owned();
+    let Some(anchor) = config.anchor else {
+        return Ok(());
+    };
+    let (embed, components) = config.build_entrypoint(entry, api).await?;
+
+    if anchor.fetch(api).await.is_ok() {
+        anchor.update(api).embeds(Some(&[embed]))?.components(Some(&components))?.await?;
+
+        return Ok(());
+    }
+
+    let message = api.http.create_message(anchor.channel_id);
+    let message = message.embeds(&[embed])?.components(&components)?.await?.model().await?;
+
+    config.anchor = Some(Anchor::from(message));
+
+    Ok(key.write_async(&config).await?)
+}
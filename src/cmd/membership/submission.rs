@@ -28,7 +28,11 @@ use crate::util::{Anchor, DataId, Result, BRANDING};
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Storage)]
 #[format(Compress<MsgPack, 6>)]
 // #[format(Compress<doop_storage::Toml, 6>)]
-#[location("{}/{}/{}", &'static str, Id<GuildMarker>, Id<UserMarker>)]
+#[location("membership/{}/{}", Id<GuildMarker>, Id<UserMarker>)]
+#[namespace(0)]
+#[version(0)]
+#[cache(1024)]
+#[index(status.kind)]
 pub struct Submission {
     /// The applicant's identifier.
     pub id: Id<UserMarker>,
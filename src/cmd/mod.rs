@@ -19,12 +19,16 @@ use twilight_model::id::Id;
 use crate::bot::interaction::{CommandCtx, ComponentCtx, ModalCtx};
 use crate::util::{DataId, Result};
 
+/// The data command.
+pub mod data;
 /// The embed command.
 pub mod embed;
 /// The help command.
 pub mod help;
 /// The data command.
 pub mod lang;
+/// The log command.
+pub mod log;
 /// The membership command.
 pub mod membership;
 /// The ping command.
@@ -58,9 +62,11 @@ macro_rules! init_registry {
 }
 
 init_registry![
+    self::data::entry,
     self::embed::entry,
     self::help::entry,
     self::lang::entry,
+    self::log::entry,
     self::membership::entry,
     self::ping::entry,
     self::role::entry
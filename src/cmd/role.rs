@@ -3,7 +3,7 @@ use std::num::NonZeroU64;
 use anyhow::bail;
 use doop_localizer::localize;
 use doop_macros::Storage;
-use doop_storage::{Compress, MsgPack, Stored};
+use doop_storage::{Compress, MsgPack, Registry, Stored, Value};
 use serde::{Deserialize, Serialize};
 use twilight_model::application::command::{
     CommandOptionChoice, CommandOptionChoiceValue, CommandOptionType,
@@ -18,7 +18,7 @@ use crate::bot::interaction::{CommandCtx, ComponentCtx};
 use crate::cmd::{CommandEntry, CommandOptionResolver, OnCommand, OnComplete, OnComponent};
 use crate::util::builder::{ActionRowBuilder, ButtonBuilder};
 use crate::util::extension::ReactionTypeExtension;
-use crate::util::traits::PreferLocale;
+use crate::util::traits::{PreferLocale, WithinQuota};
 use crate::util::{DataId, Result, BRANDING};
 
 crate::register_command! {
@@ -64,6 +64,22 @@ crate::register_command! {
     }
 }
 
+/// Registers the locations of the role command's stored types within the given registry.
+#[must_use]
+pub fn register_stored(registry: Registry) -> Registry {
+    registry.with::<Selectors>()
+}
+
+/// Accounts for the role command's stored types within their guilds' storage usage, returning the
+/// number of accounted entries.
+///
+/// # Errors
+///
+/// This function will return an error if any entry could not be accounted for.
+pub fn account_stored() -> Result<usize> {
+    Ok(Selectors::account()?)
+}
+
 /// A role selector.
 #[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
 struct Selector {
@@ -101,6 +117,8 @@ impl Selector {
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Storage)]
 #[format(Compress<MsgPack, 5>)]
 #[location("role/{}/{}", Id<GuildMarker>, Id<UserMarker>)]
+#[namespace(0)]
+#[version(0)]
 struct Selectors {
     inner: Vec<Selector>,
 }
@@ -219,9 +237,8 @@ async fn create<'api: 'evt, 'evt>(
 
     let locale = ctx.event.author().preferred_locale();
     let selectors = Selectors::stored((guild_id, user.id));
-    let mut selectors = selectors.read_or_default();
 
-    if selectors.get().len() >= 25 {
+    if selectors.read_async().await.is_ok_and(|s| s.get().len() >= 25) {
         return ctx.failure(locale, format!("{}.max_len", cmd.entry().name), true).await;
     }
 
@@ -237,10 +254,14 @@ async fn create<'api: 'evt, 'evt>(
     };
 
     let selector = Selector { id: role_id, icon: icon.into(), name: name.into_boxed_str() };
+    // The length is checked again, as the list may have changed while the role was fetched.
+    let result =
+        selectors.update_or_default_async(move |s| s.len() < 25 && s.insert(selector)).await;
+    let Some(inserted) = result.within_quota()? else {
+        return ctx.quota_exceeded(locale).await;
+    };
 
-    if selectors.get_mut().insert(selector) {
-        selectors.write()?;
-
+    if inserted {
         ctx.success(locale, format!("{}.created", cmd.entry().name), false).await
     } else {
         ctx.failure(locale, format!("{}.exists", cmd.entry().name), false).await
@@ -261,16 +282,25 @@ async fn remove<'api: 'evt, 'evt>(
 
     let locale = ctx.event.author().preferred_locale();
     let selectors = Selectors::stored((guild_id, user.id));
-    let Ok(mut selectors) = selectors.read() else {
-        return ctx.failure(locale, format!("{}.empty", cmd.entry().name), false).await;
-    };
-
-    if selectors.get_mut().remove(role_id).is_some() {
-        if selectors.get().is_empty() { selectors.remove() } else { selectors.write() }?;
+    let removed = selectors
+        .locked_async(move |selectors| {
+            let Ok(mut selectors) = selectors.read() else {
+                return Ok(None);
+            };
+            let removed = selectors.get_mut().remove(role_id).is_some();
+
+            if removed {
+                if selectors.get().is_empty() { selectors.remove() } else { selectors.write() }?;
+            }
+
+            Ok(Some(removed))
+        })
+        .await?;
 
-        ctx.success(locale, format!("{}.removed", cmd.entry().name), false).await
-    } else {
-        ctx.failure(locale, format!("{}.missing", cmd.entry().name), false).await
+    match removed {
+        Some(true) => ctx.success(locale, format!("{}.removed", cmd.entry().name), false).await,
+        Some(false) => ctx.failure(locale, format!("{}.missing", cmd.entry().name), false).await,
+        None => ctx.failure(locale, format!("{}.empty", cmd.entry().name), false).await,
     }
 }
 
@@ -287,7 +317,7 @@ async fn view<'api: 'evt, 'evt>(
 
     let locale = ctx.event.author().preferred_locale();
     let selectors = Selectors::stored((guild_id, user.id));
-    let Ok(selectors) = selectors.read() else {
+    let Ok(selectors) = selectors.read_async().await else {
         return ctx.failure(locale, format!("{}.empty", cmd.entry().name), false).await;
     };
 
@@ -321,7 +351,7 @@ async fn send<'api: 'evt, 'evt>(
 
     let locale = ctx.event.author().preferred_locale();
     let selectors = Selectors::stored((guild_id, user.id));
-    let Ok(selectors) = selectors.read() else {
+    let Ok(selectors) = selectors.read_async().await else {
         return ctx.failure(locale, format!("{}.empty", cmd.entry().name), false).await;
     };
 
@@ -329,7 +359,7 @@ async fn send<'api: 'evt, 'evt>(
     let embed = EmbedBuilder::new().color(BRANDING).title(text).build();
 
     ctx.api.http.create_message(channel_id).embeds(&[embed])?.components(&components)?.await?;
-    selectors.remove()?;
+    selectors.remove_async().await?;
 
     ctx.success(locale, format!("{}.finished", cmd.entry().name), false).await
 }
@@ -398,12 +428,12 @@ async fn fill_missing_role<'api: 'evt, 'evt>(
     };
 
     let selectors = Selectors::stored((guild_id, user.id));
-    let selectors = selectors.read_or_default();
+    let selectors = selectors.read_async().await.map(Value::get_owned).unwrap_or_default();
     let roles = ctx.api.http.roles(guild_id).await?.model().await?;
     let value = value.to_lowercase();
 
     let options = roles.into_iter().filter_map(|role| {
-        if selectors.get().contains(role.id)
+        if selectors.contains(role.id)
             || !role.name.to_lowercase().contains(&value)
             // ignore @everyone
             || role.id.cast() == guild_id
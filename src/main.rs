@@ -3,9 +3,12 @@
 #![warn(clippy::nursery, clippy::todo, clippy::pedantic, missing_docs)]
 #![allow(clippy::module_name_repetitions)]
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use doop_logger::{info, Config};
+use doop_logger::{info, warn, Config, Level, Rotation};
+use doop_storage::{Quota, Sqlite, Swept};
+use doop_threads::{AutoJoin, Consumer, HandledThread, SenderThread};
 use futures_util::future::{select, Either};
 use futures_util::pin_mut;
 use tokio::runtime::Builder;
@@ -15,11 +18,16 @@ use crate::util::{arguments, Arguments, Result};
 
 /// Defines and implements the bot's client and event handlers.
 pub mod bot;
+/// Defines and implements the bot's command-line subcommands.
+pub mod cli;
 /// Defines and implements the bot's commands.
 pub mod cmd;
 /// Defines and implements commonly-used utilities.
 pub mod util;
 
+/// The type of the returned storage sweeper thread handle.
+type SweepThread = AutoJoin<Consumer<(), ()>, ()>;
+
 /// The application's entrypoint.
 ///
 /// # Errors
@@ -32,7 +40,11 @@ pub fn main() -> Result {
 
     info!("initialized logging thread")?;
 
-    install_storage(arguments);
+    install_bridges(arguments)?;
+
+    info!("initialized dependency logging")?;
+
+    install_storage(arguments)?;
 
     info!("initialized storage directory")?;
 
@@ -46,6 +58,26 @@ pub fn main() -> Result {
         info!("loaded environment variables")?;
     }
 
+    if let Some(command) = &arguments.command {
+        return crate::cli::run(command);
+    }
+
+    // this needs to be assigned to prevent other processes from writing while the bot runs.
+    let _instance = crate::util::lock_instance()?;
+
+    if let Some(count) = crate::cmd::membership::reindex_stored()? {
+        info!("rebuilt {count} indexed storage entries")?;
+    }
+
+    let count = crate::cmd::membership::account_stored()? + crate::cmd::role::account_stored()?;
+
+    info!("accounted for {count} storage entries within quotas")?;
+
+    // this needs to be assigned to prevent the thread from joining immediately.
+    let _st = install_sweeper(arguments)?;
+
+    info!("initialized storage sweeper")?;
+
     Builder::new_multi_thread().enable_all().build()?.block_on(main_async())
 }
 
@@ -77,6 +109,10 @@ pub async fn main_async() -> Result {
         Either::Right(_) => info!("received termination signal")?,
     }
 
+    for (name, stats) in crate::cmd::membership::cache_stats() {
+        info!("{name} cache: {stats}")?;
+    }
+
     Ok(info!("stopped client process")?)
 }
 
@@ -87,22 +123,120 @@ pub async fn main_async() -> Result {
 /// This function will return an error if log(s) failed to output.
 fn install_logger(arguments: &Arguments) -> std::io::Result<doop_logger::LogThread> {
     let dir = arguments.log_output_dir.clone().unwrap_or_else(|| PathBuf::from("log").into());
+    // Debug logs are hidden by default in release builds, but may still be enabled at runtime.
+    let level = if cfg!(debug_assertions) { Level::DEBUG } else { Level::INFO };
     let config = Config {
         print: !arguments.log_no_print,
         write: !arguments.log_no_write,
         color: !arguments.log_no_color,
         queue_size: arguments.log_queue_capacity.unwrap_or(8),
         stale_time: arguments.log_queue_timeout.unwrap_or(20),
+        format: arguments.log_format.unwrap_or_default(),
+        print_level: arguments.log_print_level.unwrap_or(level),
+        write_level: arguments.log_write_level.unwrap_or(level),
+        rotation: Rotation {
+            max_size: arguments.log_rotate_size,
+            daily: arguments.log_rotate_daily,
+            compress: arguments.log_compress,
+            max_age: arguments.log_retain_days,
+            max_files: arguments.log_retain_files,
+        },
     };
 
     doop_logger::install(config, dir)
 }
 
-/// Installs the storage directory.
-fn install_storage(arguments: &Arguments) {
+/// Installs the `log` and `tracing` bridges, forwarding logs emitted by dependencies into the logger.
+///
+/// # Errors
+///
+/// This function will return an error if either bridge has already been installed.
+fn install_bridges(arguments: &Arguments) -> Result {
+    let targets = arguments.log_targets.clone().unwrap_or_default();
+
+    doop_logger::install_log_bridge(targets.clone())?;
+    doop_logger::install_tracing_bridge(targets)?;
+
+    Ok(())
+}
+
+/// Installs the storage directory, or the storage database if one is configured, alongside the
+/// configured storage quota.
+///
+/// # Errors
+///
+/// This function will return an error if the storage directory could not be recovered, or if the
+/// storage database could not be opened or imported.
+fn install_storage(arguments: &Arguments) -> Result {
     let dir = arguments.data_dir.clone().unwrap_or_else(|| PathBuf::from("res").into());
 
-    doop_storage::install(dir);
+    doop_storage::install_quota(Quota {
+        bytes: arguments.data_quota_bytes,
+        keys: arguments.data_quota_keys,
+    });
+
+    if let Some(path) = arguments.data_database.as_deref() {
+        let database = Sqlite::open(path)?;
+        let lang = arguments.l18n_map_dir.clone().unwrap_or_else(|| dir.join("lang").into());
+        // The database and its journals may be stored within the data directory itself.
+        let journals = ["-wal", "-shm", "-journal"].map(|suffix| {
+            let mut journal = path.as_os_str().to_owned();
+
+            journal.push(suffix);
+
+            PathBuf::from(journal)
+        });
+        let filter = |p: &Path| {
+            let p = dir.join(p);
+
+            !p.starts_with(&lang) && p != path && !journals.contains(&p)
+        };
+
+        // Existing data is only imported once, and the import is retried until it completes.
+        if let Some(imported) = database.import(&dir, filter)? {
+            info!("imported {} entries into the storage database", imported.len())?;
+        }
+
+        doop_storage::install_backend(database)?;
+
+        return Ok(());
+    }
+
+    for path in &*doop_storage::install(dir)? {
+        warn!("removed incomplete write: '{}'", path.display())?;
+    }
+
+    Ok(())
+}
+
+/// Installs the storage sweeper, which periodically removes expired data.
+///
+/// # Errors
+///
+/// This function will return an error if the thread could not be spawned.
+fn install_sweeper(arguments: &Arguments) -> std::io::Result<SweepThread> {
+    let interval = Duration::from_secs(arguments.data_sweep_interval.unwrap_or(60));
+
+    let thread = Consumer::spawn("sweeper", move |receiver| {
+        // Any message, or the sender being dropped, closes the thread.
+        while receiver.recv_timeout(interval).is_err_and(|e| e.is_timeout()) {
+            match doop_storage::sweep_expired() {
+                Ok(Swept { removed, bytes }) if removed > 0 => {
+                    // Caches don't observe swept entries, so they may still hold removed values.
+                    crate::cmd::membership::clear_caches();
+
+                    info!("removed {removed} expired entries, reclaiming {bytes} bytes").ok();
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    warn!("failed to remove expired entries: {error}").ok();
+                }
+            }
+        }
+    })?;
+
+    // If the call to `send` fails the thread has already closed.
+    Ok(thread.auto_cleaned(|thread| thread.send(()).unwrap_or_default()))
 }
 
 /// Installs the localizer instance.
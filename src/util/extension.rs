@@ -96,20 +96,27 @@ where
 
 /// Provides type extensions for [`Interaction`]s.
 pub trait InteractionExtension {
+    /// Provides a short name for the interaction's type.
+    fn kind_name(&self) -> &'static str;
+
     /// Provides a marker string for the interaction.
     fn marker(&self) -> String;
 }
 
 impl InteractionExtension for Interaction {
-    fn marker(&self) -> String {
-        let kind = match self.kind {
+    fn kind_name(&self) -> &'static str {
+        match self.kind {
             InteractionType::Ping => "ping",
             InteractionType::ApplicationCommand => "command",
             InteractionType::MessageComponent => "component",
             InteractionType::ApplicationCommandAutocomplete => "complete",
             InteractionType::ModalSubmit => "modal",
             _ => "unknown",
-        };
+        }
+    }
+
+    fn marker(&self) -> String {
+        let kind = self.kind_name();
 
         self.author_id().map_or_else(
             || format!("<{kind} #{}>", self.id),
@@ -119,6 +126,11 @@ impl InteractionExtension for Interaction {
 }
 
 impl InteractionExtension for InteractionCreate {
+    #[inline]
+    fn kind_name(&self) -> &'static str {
+        self.0.kind_name()
+    }
+
     #[inline]
     fn marker(&self) -> String {
         self.0.marker()
@@ -3,11 +3,12 @@ use std::fmt::{Display, Formatter};
 use std::path::Path;
 use std::str::FromStr;
 use std::sync::OnceLock;
+use std::time::Duration;
 
 use anyhow::bail;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use doop_localizer::Locale;
-use doop_storage::{Compress, Key, MsgPack};
+use doop_storage::{Backend, Compress, FileSystem, Key, Lock, MsgPack};
 use serde::{Deserialize, Serialize};
 use twilight_http::request::channel::message::UpdateMessage;
 use twilight_model::channel::Message;
@@ -37,6 +38,28 @@ pub fn arguments() -> &'static Arguments {
     ARGUMENTS.get_or_init(Arguments::parse)
 }
 
+/// The path, relative to the storage directory, whose lock is held by the running bot.
+const INSTANCE_LOCK: &str = "instance";
+
+/// Acquires the lock held by the running bot within the storage directory, which prevents other
+/// processes from writing into storage while the bot is running.
+///
+/// The lock is always placed within the storage directory, as the database backend's locks are
+/// only held within a single process.
+///
+/// # Errors
+///
+/// This function will return an error if the lock is held by another process, or if it could not
+/// be acquired.
+pub fn lock_instance() -> Result<Lock> {
+    let dir = arguments().data_dir.clone().unwrap_or_else(|| Path::new("res").into());
+
+    match FileSystem::new(dir).try_lock(Path::new(INSTANCE_LOCK))? {
+        Some(lock) => Ok(lock),
+        None => bail!("the storage directory is in use; the bot must be stopped first"),
+    }
+}
+
 /// Discord content delivery network endpoint base URL.
 pub const CDN_URL: &str = "https://cdn.discordapp.com";
 /// Discord's emoji repository's base URL.
@@ -72,6 +95,33 @@ pub struct Arguments {
     /// The logger's file output directory.
     #[arg(long = "log-dir")]
     pub log_output_dir: Option<Box<Path>>,
+    /// The logger's file output format.
+    #[arg(long = "log-format")]
+    pub log_format: Option<doop_logger::Format>,
+    /// The minimum level of logs output to the console.
+    #[arg(long = "log-print-level")]
+    pub log_print_level: Option<doop_logger::Level>,
+    /// The minimum level of logs output to the log file.
+    #[arg(long = "log-write-level")]
+    pub log_write_level: Option<doop_logger::Level>,
+    /// The minimum level of logs output by dependencies, as comma-separated `target=level` pairs.
+    #[arg(long = "log-targets")]
+    pub log_targets: Option<doop_logger::Targets>,
+    /// The maximum size of a log file in bytes before a new file is started.
+    #[arg(long = "log-rotate-size")]
+    pub log_rotate_size: Option<u64>,
+    /// Starts a new log file every day.
+    #[arg(long = "log-rotate-daily")]
+    pub log_rotate_daily: bool,
+    /// Compresses previous log files using gzip.
+    #[arg(long = "log-compress")]
+    pub log_compress: bool,
+    /// The number of days that previous log files are kept for.
+    #[arg(long = "log-retain-days")]
+    pub log_retain_days: Option<u64>,
+    /// The maximum number of kept log files, including the current file.
+    #[arg(long = "log-retain-files")]
+    pub log_retain_files: Option<usize>,
 
     /// The localizer's preferred directory.
     #[arg(short = 'l', long = "prefer-locale")]
@@ -83,6 +133,81 @@ pub struct Arguments {
     /// The preferred data storage directory.
     #[arg(short = 'o', long = "data-dir")]
     pub data_dir: Option<Box<Path>>,
+    /// The preferred data storage database, which replaces the storage directory if set.
+    #[arg(long = "data-database")]
+    pub data_database: Option<Box<Path>>,
+    /// The interval between sweeps of expired data in seconds.
+    #[arg(long = "data-sweep-interval")]
+    pub data_sweep_interval: Option<u64>,
+    /// The interval between checks for data that was modified outside of the bot in seconds.
+    #[arg(long = "data-watch-interval")]
+    pub data_watch_interval: Option<u64>,
+    /// The maximum number of bytes that each server may store.
+    #[arg(long = "data-quota-bytes")]
+    pub data_quota_bytes: Option<u64>,
+    /// The maximum number of entries that each server may store.
+    #[arg(long = "data-quota-keys")]
+    pub data_quota_keys: Option<u64>,
+
+    /// The subcommand to run instead of starting the bot.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// The bot's command-line subcommands.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Saves a snapshot of the storage directory into an archive.
+    Backup {
+        /// The archive's output path.
+        path: Box<Path>,
+        /// Compresses the archive.
+        #[arg(short = 'c', long = "compress")]
+        compress: bool,
+        /// Encrypts the archive using the storage encryption keyring.
+        #[arg(short = 'x', long = "encrypt")]
+        encrypt: bool,
+    },
+    /// Restores a snapshot archive into the empty storage directory.
+    Restore {
+        /// The archive's input path.
+        path: Box<Path>,
+    },
+    /// Inspects and maintains the storage directory.
+    Storage {
+        /// The storage subcommand to run.
+        #[command(subcommand)]
+        command: StorageCommand,
+    },
+}
+
+/// The bot's storage subcommands.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq, Subcommand)]
+pub enum StorageCommand {
+    /// Verifies that every stored file belongs to a known location and can be decoded.
+    Verify {
+        /// Moves every file that failed verification into the given directory.
+        #[arg(long = "quarantine")]
+        quarantine: Option<Box<Path>>,
+    },
+    /// Decodes a stored file and prints its contents as JSON.
+    Dump {
+        /// The file's location within the storage directory, such as `role/1/2`.
+        location: Box<str>,
+    },
+    /// Encodes JSON into a stored file using the file's original format.
+    ///
+    /// This refuses to run while the bot is running, as the bot would not see the change.
+    Load {
+        /// The file's location within the storage directory, such as `role/1/2`.
+        location: Box<str>,
+        /// The JSON file to load, which defaults to the standard input.
+        path: Option<Box<Path>>,
+    },
+    /// Re-encrypts every encrypted stored file that was not encrypted using the active key.
+    RotateKeys,
 }
 
 /// Represents a single message's location.
@@ -194,6 +319,8 @@ pub struct DataId {
 impl DataId {
     /// The maximum length of an identifier in bytes.
     pub const MAX_LEN: usize = 100;
+    /// The duration after which stored component data expires.
+    pub const KEY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
     /// The character used to separate each part of the identifier.
     pub const PART_SEP: char = '$';
     /// The character used to serparate data values within the identifier.
@@ -227,14 +354,21 @@ impl DataId {
         self.data.get(index).map(|b| &(**b))
     }
 
-    /// Returns the storage key of this [`CId`].
+    /// Returns the storage key of this [`CId`] within the given guild.
+    ///
+    /// Component state is abandoned whenever a user does not finish an interaction, so every value
+    /// expires [`KEY_TTL`](<DataId::KEY_TTL>) after it was last written. Values count towards the
+    /// guild's storage quota.
     #[inline]
     #[must_use]
-    pub fn key<T>(&self) -> Option<Key<T, Compress<MsgPack, 4>>>
+    pub fn key<T>(&self, guild_id: Id<GuildMarker>) -> Option<Key<T, Compress<MsgPack, 4>>>
     where
         T: Serialize + for<'de> Deserialize<'de>,
     {
-        Some(format!(".cid/{}/{}/{}", self.name.0, self.name.1, self.uuid?).into())
+        let (name, kind, uuid) = (&self.name.0, &self.name.1, self.uuid?);
+        let key: Key<_, _> = format!(".cid/{guild_id}/{name}/{kind}/{uuid}").into();
+
+        Some(key.with_ttl(Self::KEY_TTL).with_namespace(guild_id.to_string()))
     }
 
     /// Generates a new random storage key for this [`CId`].
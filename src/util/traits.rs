@@ -1,5 +1,6 @@
 use anyhow::bail;
 use doop_localizer::{localizer, Locale};
+use doop_storage::Format;
 use time::ext::NumericalDuration;
 use time::macros::datetime;
 use time::OffsetDateTime;
@@ -307,3 +308,31 @@ impl PreferLocale for User {
             .unwrap_or_else(|| *localizer().preferred_locale())
     }
 }
+
+/// Provides a method that separates exceeded storage quotas from other storage errors.
+pub trait WithinQuota {
+    /// The type of the contained value.
+    type Output;
+
+    /// Returns the contained value, or [`None`] if a storage quota was exceeded.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the result contains any other storage error.
+    fn within_quota(self) -> anyhow::Result<Option<Self::Output>>;
+}
+
+impl<T, F: Format> WithinQuota for Result<T, doop_storage::Error<F>>
+where
+    doop_storage::Error<F>: Into<anyhow::Error>,
+{
+    type Output = T;
+
+    fn within_quota(self) -> anyhow::Result<Option<Self::Output>> {
+        match self {
+            Ok(value) => Ok(Some(value)),
+            Err(doop_storage::Error::Quota(..)) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+}